@@ -0,0 +1,157 @@
+/*!
+* Analysis of the whole state space by breadth-first search from solved state.
+* Layers are kept in memory, so complete search is feasible only for puzzles up to size 3,
+* bigger ones have to be limited by distance (or searched by [ExternalBfs](crate::external_bfs)).
+*/
+
+use std::collections::HashSet;
+
+use crate::PuzzleState;
+
+/// Number of states at each distance from solution.
+#[derive(Debug, Clone)]
+pub struct LayerAnalysis<const PUZZLE_SIZE: usize> {
+    layer_sizes: Vec<usize>,
+    last_layer: Vec<PuzzleState<PUZZLE_SIZE>>,
+    complete: bool,
+}
+
+impl<const PUZZLE_SIZE: usize> LayerAnalysis<PUZZLE_SIZE> {
+    /// Returns number of states at each distance (index is distance).
+    pub fn layer_sizes(&self) -> &[usize] {
+        &self.layer_sizes
+    }
+
+    /// Returns number of all found states.
+    pub fn states_count(&self) -> usize {
+        self.layer_sizes.iter().sum()
+    }
+
+    /// Returns distance of the last found layer.
+    pub fn max_distance(&self) -> usize {
+        self.layer_sizes.len() - 1
+    }
+
+    /**
+     * Checks if all states reachable from solution were found. Otherwise search was stopped by
+     * distance limit.
+     */
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /**
+     * Returns states in the last found layer (sorted by [PuzzleState::rank]). If analysis is
+     * complete, they are the hardest states (antipodes of solved state).
+     */
+    pub fn last_layer(&self) -> &[PuzzleState<PUZZLE_SIZE>] {
+        &self.last_layer
+    }
+}
+
+/**
+* Counts states at each distance from solution using BFS up to `max_distance` (or until all
+* states are found).
+*/
+pub fn analyze_layers<const PUZZLE_SIZE: usize>(
+    max_distance: Option<usize>,
+) -> LayerAnalysis<PUZZLE_SIZE> {
+    let mut previous_layer = HashSet::new();
+    let mut layer = HashSet::from([PuzzleState::<PUZZLE_SIZE>::solved()]);
+    let mut layer_sizes = vec![1];
+    let mut complete = false;
+
+    while max_distance.is_none_or(|max_distance| layer_sizes.len() <= max_distance) {
+        let mut next_layer = HashSet::new();
+
+        // Board graph is bipartite, so neighbours are either in previous or in next layer.
+        for puzzle_state in &layer {
+            for neighbour in puzzle_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                if !previous_layer.contains(&neighbour_state) {
+                    next_layer.insert(neighbour_state);
+                }
+            }
+        }
+
+        if next_layer.is_empty() {
+            complete = true;
+            break;
+        }
+
+        layer_sizes.push(next_layer.len());
+        previous_layer = layer;
+        layer = next_layer;
+    }
+
+    let mut last_layer: Vec<_> = layer.into_iter().collect();
+    last_layer.sort_by_key(PuzzleState::rank);
+
+    LayerAnalysis {
+        layer_sizes,
+        last_layer,
+        complete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::{ManhattanDistance, PerfectHeuristic};
+    use crate::solve_with_heuristic;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const BIGGEST_PUZZLE_SIZE: usize = 4;
+
+    #[test]
+    fn small_puzzle_layers_work() {
+        let layer_analysis = analyze_layers::<PUZZLE_SIZE>(None);
+
+        assert!(layer_analysis.is_complete());
+        assert_eq!(&[1, 2, 2, 2, 2, 2, 1], layer_analysis.layer_sizes());
+        assert_eq!(6, layer_analysis.max_distance());
+        assert_eq!(
+            vec!["[, 3, 2, 1]".parse::<PuzzleState<PUZZLE_SIZE>>().unwrap()],
+            layer_analysis.last_layer()
+        );
+    }
+
+    #[test]
+    fn all_solvable_states_are_found() {
+        let layer_analysis = analyze_layers::<BIGGER_PUZZLE_SIZE>(None);
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+
+        assert!(layer_analysis.is_complete());
+        assert_eq!(
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solvable_states_count() as usize,
+            layer_analysis.states_count()
+        );
+        assert_eq!(
+            perfect_heuristic.distance_histogram(),
+            layer_analysis.layer_sizes()
+        );
+        assert_eq!(31, layer_analysis.max_distance());
+
+        for antipodal_state in layer_analysis.last_layer() {
+            assert!(antipodal_state.is_solvable());
+
+            // Cross-check of solver optimality.
+            let solution =
+                solve_with_heuristic(*antipodal_state, &ManhattanDistance::new()).unwrap();
+
+            assert_eq!(31, solution.steps().len());
+        }
+    }
+
+    #[test]
+    fn depth_limit_works() {
+        let layer_analysis = analyze_layers::<BIGGEST_PUZZLE_SIZE>(Some(5));
+
+        assert!(!layer_analysis.is_complete());
+        assert_eq!(&[1, 2, 4, 10, 24, 54], layer_analysis.layer_sizes());
+        assert_eq!(54, layer_analysis.last_layer().len());
+    }
+}
@@ -1,10 +1,11 @@
 //! State used when performing A* algorithm to find solution of sliding puzzle game.
 
-use std::collections::HashMap;
-
+use crate::closed_list::LastDirections;
 use crate::heuristics::Heuristic;
+use crate::puzzle_state::coordinates::BoardCoordinates;
 use crate::puzzle_state::direction::Direction;
-use crate::puzzle_state::puzzle_move::Move;
+use crate::puzzle_state::obstacles::Solvability;
+use crate::puzzle_state::puzzle_move::{Move, Neighbours};
 use crate::puzzle_state::PuzzleState;
 
 /// A* state errors.
@@ -21,47 +22,75 @@ pub struct AstarState<const PUZZLE_SIZE: usize> {
     last_direction: Option<Direction>,
     distance_from_start: u8,
     puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    /// Position of blank moved by the last move (of the first blank for initial state).
+    moved_blank: BoardCoordinates<PUZZLE_SIZE>,
 }
 
 impl<const PUZZLE_SIZE: usize> AstarState<PUZZLE_SIZE> {
-    /// Create initial [AstarState] from initial [PuzzleState].
+    /**
+     * Create initial [AstarState] from initial [PuzzleState]. It fails only if
+     * [PuzzleState::solvability] proves that state is unsolvable.
+     */
     pub fn inital(
         puzzle_state: PuzzleState<PUZZLE_SIZE>,
         heuristic: &dyn Heuristic<PUZZLE_SIZE>,
     ) -> Result<Self, AstarStateError> {
-        if !puzzle_state.is_solvable() {
+        if puzzle_state.solvability() == Solvability::Unsolvable {
             Err(AstarStateError::InitialStateNotSolvable)
         } else {
-            Ok(AstarState {
-                f_value: puzzle_state.calculate_heuristic(heuristic),
-                last_direction: None,
-                distance_from_start: 0,
-                puzzle_state,
-            })
+            Ok(Self::initial_unchecked(puzzle_state, heuristic))
         }
     }
 
-    /// Returns [AstarState] after move to given neighbour.
+    /**
+     * Create initial [AstarState] without checking solvability (used for goals other than
+     * solved state).
+     */
+    pub fn initial_unchecked(
+        puzzle_state: PuzzleState<PUZZLE_SIZE>,
+        heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    ) -> Self {
+        AstarState {
+            f_value: puzzle_state.calculate_heuristic(heuristic),
+            last_direction: None,
+            distance_from_start: 0,
+            puzzle_state,
+            moved_blank: puzzle_state.blank_position(),
+        }
+    }
+
+    /// Returns [AstarState] after given `neighbour` move.
     pub fn moved_to_neighbour(
         &self,
-        direction: Direction,
-        obtained_state: PuzzleState<PUZZLE_SIZE>,
+        neighbour: Move<PUZZLE_SIZE>,
         heuristic: &dyn Heuristic<PUZZLE_SIZE>,
     ) -> AstarState<PUZZLE_SIZE> {
+        let (direction, obtained_state) = neighbour.into_direction_and_puzzle_state();
         let neighbour_shortest_path_len = self.distance_from_start + 1;
+        let previous_blank = obtained_state
+            .topology()
+            .neighbour(neighbour.blank_position(), direction.opposite())
+            .expect("Blank has to come from board cell.");
+        let heuristic_value = obtained_state.calculate_heuristic_after_move(
+            heuristic,
+            self.f_value - self.distance_from_start,
+            neighbour.blank_position(),
+            previous_blank,
+        );
         // There can't occur overflow here for puzzle of size 4.
-        let f_value = neighbour_shortest_path_len + obtained_state.calculate_heuristic(heuristic);
+        let f_value = neighbour_shortest_path_len + heuristic_value;
 
         AstarState {
             f_value,
             last_direction: Some(direction),
             distance_from_start: neighbour_shortest_path_len,
             puzzle_state: obtained_state,
+            moved_blank: neighbour.blank_position(),
         }
     }
 
     /// Create neighbours of current A* state.
-    pub fn neighbours(&self) -> Vec<Move<PUZZLE_SIZE>> {
+    pub fn neighbours(&self) -> Neighbours<PUZZLE_SIZE> {
         self.puzzle_state.neighbours()
     }
 
@@ -75,28 +104,76 @@ impl<const PUZZLE_SIZE: usize> AstarState<PUZZLE_SIZE> {
         self.puzzle_state
     }
 
+    /// Accessor for `f_value` field.
+    pub fn f_value(&self) -> u8 {
+        self.f_value
+    }
+
+    /// Accessor for `distance_from_start` field (g value).
+    pub fn distance_from_start(&self) -> u8 {
+        self.distance_from_start
+    }
+
+    /// Returns heuristic value of state (h value).
+    pub fn heuristic_value(&self) -> u8 {
+        self.f_value - self.distance_from_start
+    }
+
     /// Accessor for `last_direction` field.
     pub fn last_direction(&self) -> Option<Direction> {
         self.last_direction
     }
 
+    /// Accessor for `moved_blank` field.
+    pub fn moved_blank(&self) -> BoardCoordinates<PUZZLE_SIZE> {
+        self.moved_blank
+    }
+
     /// Creates route leading from first puzzle_state to current one.
     pub fn create_route(
         &self,
-        last_directions: &HashMap<PuzzleState<PUZZLE_SIZE>, Option<Direction>>,
+        last_directions: &impl LastDirections<PUZZLE_SIZE>,
     ) -> Vec<Direction> {
-        let mut curr_puzzle_state = self.puzzle_state.clone();
+        self.create_route_with_moved_tiles(last_directions)
+            .into_iter()
+            .map(|(direction, _)| direction)
+            .collect()
+    }
+
+    /**
+     * Creates route leading from first puzzle_state to current one together with number of tile
+     * moved by every step (with several blanks direction alone doesn't determine move).
+     */
+    pub fn create_route_with_moved_tiles(
+        &self,
+        last_directions: &impl LastDirections<PUZZLE_SIZE>,
+    ) -> Vec<(Direction, u8)> {
+        let mut curr_puzzle_state = self.puzzle_state;
+        let mut curr_blank = self.moved_blank;
         let mut curr_direction = self.last_direction;
         let mut reversed_route = vec![];
 
         while let Some(direction) = curr_direction {
-            reversed_route.push(direction);
-
             let opposite_direction = direction.opposite();
-            curr_puzzle_state = curr_puzzle_state.create_neighbour_move_state(opposite_direction);
-            curr_direction = *last_directions
-                .get(&curr_puzzle_state)
+            let moved_tile_coordinates = curr_puzzle_state
+                .topology()
+                .neighbour(curr_blank, opposite_direction)
+                .expect("Blank has to come from board cell.");
+            let moved_tile = curr_puzzle_state
+                .number_at(moved_tile_coordinates)
+                .expect("Tile has to be moved to blank cell.");
+
+            reversed_route.push((direction, moved_tile));
+
+            curr_puzzle_state = curr_puzzle_state
+                .create_blank_move_state(curr_blank, opposite_direction)
+                .expect("Move back to previous state has to be possible.");
+            curr_direction = last_directions
+                .last_direction(&curr_puzzle_state)
                 .expect("There has to be entry in last_directions for puzzle route.");
+            curr_blank = last_directions
+                .moved_blank(&curr_puzzle_state)
+                .unwrap_or_else(|| curr_puzzle_state.blank_position());
         }
 
         reversed_route.into_iter().rev().collect()
@@ -127,7 +204,11 @@ impl<const PUZZLE_SIZE: usize> Ord for AstarState<PUZZLE_SIZE> {
 mod tests {
     use super::*;
 
+    use std::collections::HashMap;
+
+    use crate::closed_list::ClosedList;
     use crate::heuristics::dumb_heuristic::DumbHeuristic;
+    use crate::heuristics::ManhattanDistance;
 
     const BIGGER_PUZZLE_SIZE: usize = 3;
 
@@ -140,7 +221,7 @@ mod tests {
         ])
         .unwrap();
 
-        let expected_puzzle_state = puzzle_state.clone();
+        let expected_puzzle_state = puzzle_state;
 
         let astar_state_result = AstarState::inital(puzzle_state, &DumbHeuristic);
 
@@ -151,12 +232,14 @@ mod tests {
             last_direction,
             distance_from_start,
             puzzle_state,
+            moved_blank,
         } = astar_state_result.unwrap();
 
         assert_eq!(36, f_value);
         assert_eq!(None, last_direction);
         assert_eq!(0, distance_from_start);
         assert_eq!(puzzle_state, expected_puzzle_state);
+        assert_eq!(BoardCoordinates::new(2, 2), moved_blank);
     }
 
     #[test]
@@ -186,37 +269,62 @@ mod tests {
         .unwrap();
 
         let astar_state = AstarState::inital(puzzle_state, &DumbHeuristic).unwrap();
-        let mut neighbours = astar_state.neighbours();
+        let mut neighbours: Vec<_> = astar_state.neighbours().collect();
 
         let first_neighbour = neighbours.pop().unwrap();
 
-        let (direction, puzzle_state) = first_neighbour.into_direction_and_puzzle_state();
         let AstarState {
             f_value,
             last_direction,
             distance_from_start,
             puzzle_state: _,
-        } = astar_state.moved_to_neighbour(direction, puzzle_state, &DumbHeuristic);
+            moved_blank,
+        } = astar_state.moved_to_neighbour(first_neighbour, &DumbHeuristic);
 
         assert_eq!(37, f_value);
         assert_eq!(Some(Direction::Left), last_direction);
         assert_eq!(1, distance_from_start);
+        assert_eq!(BoardCoordinates::new(2, 1), moved_blank);
 
         let second_neighbour = neighbours.pop().unwrap();
 
-        let (direction, puzzle_state) = second_neighbour.into_direction_and_puzzle_state();
         let AstarState {
             f_value,
             last_direction,
             distance_from_start,
             puzzle_state: _,
-        } = astar_state.moved_to_neighbour(direction, puzzle_state, &DumbHeuristic);
+            moved_blank: _,
+        } = astar_state.moved_to_neighbour(second_neighbour, &DumbHeuristic);
 
         assert_eq!(37, f_value);
         assert_eq!(Some(Direction::Up), last_direction);
         assert_eq!(1, distance_from_start);
     }
 
+    #[test]
+    fn moved_to_neighbour_with_delta() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), Some(5), Some(6)],
+            [Some(7), None, Some(8)],
+        ])
+        .unwrap();
+
+        let astar_state = AstarState::inital(puzzle_state, &manhattan_distance).unwrap();
+
+        for neighbour in astar_state.neighbours() {
+            let (_, puzzle_state) = neighbour.into_direction_and_puzzle_state();
+            let expected_f_value = 1 + puzzle_state.calculate_heuristic(&manhattan_distance);
+
+            let AstarState { f_value, .. } =
+                astar_state.moved_to_neighbour(neighbour, &manhattan_distance);
+
+            assert_eq!(expected_f_value, f_value);
+        }
+    }
+
     #[test]
     fn create_route_works() {
         let astar_state = AstarState {
@@ -229,6 +337,7 @@ mod tests {
                 [Some(7), Some(8), None],
             ])
             .unwrap(),
+            moved_blank: BoardCoordinates::new(2, 2),
         };
 
         let mut last_directions = HashMap::new();
@@ -257,5 +366,16 @@ mod tests {
             vec![Direction::Right, Direction::Right],
             astar_state.create_route(&last_directions)
         );
+
+        let mut closed_list = ClosedList::new();
+
+        for (&puzzle_state, &last_direction) in &last_directions {
+            closed_list.insert(puzzle_state, last_direction);
+        }
+
+        assert_eq!(
+            vec![Direction::Right, Direction::Right],
+            astar_state.create_route(&closed_list)
+        );
     }
 }
@@ -0,0 +1,113 @@
+/*!
+* Batch files with many puzzle states. Each line contains one state in format used by
+* [PuzzleState] `Display` and `FromStr` implementations, e.g. `[1, 2, 3, 4, 5, 6, 7, , 8]`.
+* Empty lines and lines starting with `#` are ignored.
+*/
+
+use std::io::{self, BufRead, Write};
+
+use crate::puzzle_state::errors::PuzzleStateParseError;
+use crate::PuzzleState;
+
+const COMMENT_PREFIX: char = '#';
+
+/// Errors that can occur when reading batch file.
+#[derive(Debug)]
+pub enum BatchReadError {
+    /// Reading input failed.
+    Io(io::Error),
+    /// Line with given number (counted from 1) doesn't contain valid puzzle state.
+    Parse {
+        line_number: usize,
+        error: PuzzleStateParseError,
+    },
+}
+
+impl From<io::Error> for BatchReadError {
+    fn from(value: io::Error) -> Self {
+        BatchReadError::Io(value)
+    }
+}
+
+/// Writes `puzzle_states` in batch file format.
+pub fn write_batch<const PUZZLE_SIZE: usize>(
+    writer: &mut impl Write,
+    puzzle_states: &[PuzzleState<PUZZLE_SIZE>],
+) -> io::Result<()> {
+    for puzzle_state in puzzle_states {
+        writeln!(writer, "{puzzle_state}")?;
+    }
+
+    Ok(())
+}
+
+/// Reads puzzle states from batch file.
+pub fn read_batch<const PUZZLE_SIZE: usize>(
+    reader: impl BufRead,
+) -> Result<Vec<PuzzleState<PUZZLE_SIZE>>, BatchReadError> {
+    let mut puzzle_states = vec![];
+
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(COMMENT_PREFIX) {
+            continue;
+        }
+
+        let puzzle_state = line.parse().map_err(|error| BatchReadError::Parse {
+            line_number: line_index + 1,
+            error,
+        })?;
+
+        puzzle_states.push(puzzle_state);
+    }
+
+    Ok(puzzle_states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn batch_round_trip() {
+        let puzzle_states = vec![
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved(),
+            "[1, 4, 2, 3, , 5, 6, 7, 8]".parse().unwrap(),
+        ];
+        let mut batch = vec![];
+
+        write_batch(&mut batch, &puzzle_states).unwrap();
+
+        assert_eq!(
+            "[1, 2, 3, 4, 5, 6, 7, 8, ]\n[1, 4, 2, 3, , 5, 6, 7, 8]\n",
+            String::from_utf8(batch.clone()).unwrap()
+        );
+        assert_eq!(puzzle_states, read_batch(batch.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn comments_and_empty_lines_skipped() {
+        let batch = "# hard states\n\n[1, 4, 2, 3, , 5, 6, 7, 8]\n";
+        let puzzle_states = read_batch::<BIGGER_PUZZLE_SIZE>(batch.as_bytes()).unwrap();
+
+        assert_eq!(1, puzzle_states.len());
+    }
+
+    #[test]
+    fn invalid_line_reported() {
+        let batch = "[1, 2, 3, 4, 5, 6, 7, 8, ]\n[1, 2, 3]\n";
+        let error = read_batch::<BIGGER_PUZZLE_SIZE>(batch.as_bytes()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            BatchReadError::Parse {
+                line_number: 2,
+                error: PuzzleStateParseError::NotEnoughNumbers
+            }
+        ));
+    }
+}
@@ -1,7 +1,15 @@
 /// Used to generate statistics for different heuristics.
+/// Run with `open-lists` argument to compare open list implementations or with `moves` to
+/// measure speed of neighbour generation instead.
+use std::hint::black_box;
 use std::time::Instant;
 
-use z1::heuristics::{DisjointDatabases, ManhattanDistance};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use puzzle::heuristics::{DisjointDatabases, Heuristic, ManhattanDistance};
+use puzzle::open_list::OpenListKind;
+use puzzle::{generator, Direction, PuzzleState, SearchOptions};
 
 const PUZZLE_SIZE: usize = 4;
 const NO_OF_ITERATIONS: usize = 100;
@@ -10,7 +18,23 @@ const MAX_STEPS_BACK_START: usize = 10;
 const MAX_STEPS_BACK_STEP: usize = 5;
 const MAX_STEPS_BACK_STEPS: usize = 13;
 
+const OPEN_LIST_BENCHMARK_ITERATIONS: usize = 20;
+const OPEN_LIST_BENCHMARK_STEPS_BACK: usize = 60;
+
+const MOVES_BENCHMARK_SEED: u64 = 42;
+const MOVES_BENCHMARK_STATES: usize = 1000;
+const MOVES_BENCHMARK_ITERATIONS: usize = 1000;
+const MOVES_BENCHMARK_WALK_STEPS: usize = 1_000_000;
+
 fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("open-lists") => compare_open_lists(),
+        Some("moves") => measure_moves(),
+        _ => compare_heuristics(),
+    }
+}
+
+fn compare_heuristics() {
     let manhattan_distance = ManhattanDistance::<PUZZLE_SIZE>::new();
     let disjoint_databases = DisjointDatabases::new(false);
 
@@ -20,14 +44,14 @@ fn main() {
         let steps = MAX_STEPS_BACK_START + (steps_increment * MAX_STEPS_BACK_STEP);
 
         for _ in 0..NO_OF_ITERATIONS {
-            let random_state = z1::generate_random_puzzle_state(steps);
+            let random_state = puzzle::generate_random_puzzle_state(steps);
 
             let md_start_time = Instant::now();
-            let md_solution = z1::solve_with_heuristic(random_state, &manhattan_distance);
+            let md_solution = puzzle::solve_with_heuristic(random_state, &manhattan_distance);
             let md_runtime = Instant::now() - md_start_time;
 
             let dd_start_time = Instant::now();
-            let dd_solution = z1::solve_with_heuristic(random_state, &disjoint_databases);
+            let dd_solution = puzzle::solve_with_heuristic(random_state, &disjoint_databases);
             let dd_runtime = Instant::now() - dd_start_time;
 
             if let (Some(md_solution), Some(dd_solution)) = (md_solution, dd_solution) {
@@ -48,3 +72,158 @@ fn main() {
         }
     }
 }
+
+fn compare_open_lists() {
+    let manhattan_distance = ManhattanDistance::<PUZZLE_SIZE>::new();
+    let disjoint_databases = DisjointDatabases::new(false);
+    let heuristics: [(&str, &dyn Heuristic<PUZZLE_SIZE>); 2] =
+        [("MD", &manhattan_distance), ("DD", &disjoint_databases)];
+
+    println!("Heuristic | Open list | Solution length | Visited states | Runtime");
+
+    let mut total_runtimes = [[0; 2]; 2];
+
+    for _ in 0..OPEN_LIST_BENCHMARK_ITERATIONS {
+        let random_state = puzzle::generate_random_puzzle_state(OPEN_LIST_BENCHMARK_STEPS_BACK);
+
+        for (heuristic_index, (heuristic_name, heuristic)) in heuristics.iter().enumerate() {
+            for (open_list_index, open_list) in [OpenListKind::Heap, OpenListKind::Buckets]
+                .into_iter()
+                .enumerate()
+            {
+                let options = SearchOptions::new().with_open_list(open_list);
+
+                let start_time = Instant::now();
+                let solution = puzzle::solve_with_options(random_state, *heuristic, options)
+                    .expect("Generated puzzle state has to be solvable.");
+                let runtime = Instant::now() - start_time;
+
+                total_runtimes[heuristic_index][open_list_index] += runtime.as_millis();
+
+                println!(
+                    "{heuristic_name}: {open_list:?} {} {} {}",
+                    solution.steps().len(),
+                    solution.no_of_visited_states(),
+                    runtime.as_millis()
+                );
+            }
+        }
+    }
+
+    for (heuristic_index, (heuristic_name, _)) in heuristics.iter().enumerate() {
+        let [heap_runtime, buckets_runtime] = total_runtimes[heuristic_index];
+
+        println!(
+            "{heuristic_name} total runtime: Heap {heap_runtime} ms, Buckets {buckets_runtime} ms"
+        );
+    }
+}
+
+fn measure_moves() {
+    let mut rng = StdRng::seed_from_u64(MOVES_BENCHMARK_SEED);
+    let puzzle_states: Vec<PuzzleState<PUZZLE_SIZE>> = (0..MOVES_BENCHMARK_STATES)
+        .map(|_| generator::uniform_random_puzzle_state(&mut rng))
+        .collect();
+
+    let neighbours_runtime = measure_per_state(&puzzle_states, |puzzle_state| {
+        for neighbour in puzzle_state.neighbours() {
+            black_box(neighbour);
+        }
+    });
+    let baseline_runtime = measure_per_state(&puzzle_states, |puzzle_state| {
+        black_box(unpacked_neighbours(puzzle_state));
+    });
+
+    println!("Neighbours: {neighbours_runtime:.1} ns per state");
+    println!(
+        "Unpacked neighbours (baseline): {baseline_runtime:.1} ns per state, {:.1}x slower",
+        baseline_runtime / neighbours_runtime
+    );
+
+    let start_time = Instant::now();
+    let puzzle_state: PuzzleState<PUZZLE_SIZE> =
+        generator::random_walk_puzzle_state(MOVES_BENCHMARK_WALK_STEPS, &mut rng);
+    let runtime = Instant::now() - start_time;
+
+    black_box(puzzle_state);
+
+    println!(
+        "Random walk: {:.1} ns per step",
+        runtime.as_nanos() as f64 / MOVES_BENCHMARK_WALK_STEPS as f64
+    );
+
+    let manhattan_distance = ManhattanDistance::<PUZZLE_SIZE>::new();
+    let puzzle_state =
+        generator::random_walk_puzzle_state(OPEN_LIST_BENCHMARK_STEPS_BACK, &mut rng);
+
+    let start_time = Instant::now();
+    let solution = puzzle::solve_with_heuristic(puzzle_state, &manhattan_distance)
+        .expect("Generated puzzle state has to be solvable.");
+    let runtime = Instant::now() - start_time;
+
+    println!(
+        "MD solve: {} states in {} ms",
+        solution.no_of_visited_states(),
+        runtime.as_millis()
+    );
+}
+
+/// Returns average runtime of `generate` in nanoseconds per state of `puzzle_states`.
+fn measure_per_state(
+    puzzle_states: &[PuzzleState<PUZZLE_SIZE>],
+    generate: impl Fn(&PuzzleState<PUZZLE_SIZE>),
+) -> f64 {
+    let start_time = Instant::now();
+
+    for _ in 0..MOVES_BENCHMARK_ITERATIONS {
+        for puzzle_state in puzzle_states {
+            generate(black_box(puzzle_state));
+        }
+    }
+
+    let runtime = Instant::now() - start_time;
+    let generate_calls = puzzle_states.len() * MOVES_BENCHMARK_ITERATIONS;
+
+    runtime.as_nanos() as f64 / generate_calls as f64
+}
+
+/**
+* Generates neighbours the way it was done before blank index was tracked, as baseline for
+* [measure_moves]. Numbers are unpacked into board, blank is searched for and every neighbour is
+* packed from modified board into allocated vector. Public constructor also checks numbers, so
+* baseline is a bit slower than the original code was.
+*/
+fn unpacked_neighbours(puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> Vec<PuzzleState<PUZZLE_SIZE>> {
+    let numbers = puzzle_state.readable_numbers();
+    let (blank_row, blank_column) = (0..PUZZLE_SIZE)
+        .flat_map(|row| (0..PUZZLE_SIZE).map(move |column| (row, column)))
+        .find(|&(row, column)| numbers[row][column].is_none())
+        .expect("Puzzle state has to contain blank.");
+    let mut neighbours = vec![];
+
+    for direction in [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ] {
+        let (diff_row, diff_column) = direction.as_coordinates();
+        let swap_row = blank_row as isize + diff_row;
+        let swap_column = blank_column as isize + diff_column;
+
+        if !(0..PUZZLE_SIZE as isize).contains(&swap_row)
+            || !(0..PUZZLE_SIZE as isize).contains(&swap_column)
+        {
+            continue;
+        }
+
+        let mut new_numbers = numbers;
+
+        new_numbers[blank_row][blank_column] = numbers[swap_row as usize][swap_column as usize];
+        new_numbers[swap_row as usize][swap_column as usize] = None;
+
+        neighbours.push(PuzzleState::new(new_numbers).expect("Moved numbers have to be valid."));
+    }
+
+    neighbours
+}
@@ -0,0 +1,317 @@
+/*!
+* Closed list of A* search storing direction of last move for every visited state.
+*
+* [ClosedList] is an open-addressing hash table with linear probing, where every slot is a
+* single `u64`. Number in the last board cell is determined by the other ones, so its 4 bits
+* are replaced by last direction tag (zero marks empty slot).
+*/
+
+use std::collections::HashMap;
+use std::mem;
+
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::{Direction, PuzzleState};
+
+const INITIAL_CAPACITY: usize = 1 << 10;
+/// Table grows when it would be more than `MAX_LOAD_NUMERATOR / MAX_LOAD_DENOMINATOR` full.
+const MAX_LOAD_NUMERATOR: usize = 3;
+const MAX_LOAD_DENOMINATOR: usize = 4;
+const EMPTY_SLOT: u64 = 0;
+const TAG_WIDTH: usize = 4;
+const TAG_MASK: u64 = 0b1111;
+/// 2^64 divided by golden ratio, used for Fibonacci hashing.
+const HASH_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Mapping from visited states to direction of move leading to them.
+pub trait LastDirections<const PUZZLE_SIZE: usize> {
+    /**
+     * Returns `Some(last_direction)` if `puzzle_state` was visited (`last_direction` is `None`
+     * for initial state) or `None` otherwise.
+     */
+    fn last_direction(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> Option<Option<Direction>>;
+
+    /**
+     * Returns position of blank moved by the last move to visited `puzzle_state` with several
+     * blanks or `None` if it isn't known (then it is assumed to be [PuzzleState::blank_position]).
+     */
+    fn moved_blank(
+        &self,
+        _puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<BoardCoordinates<PUZZLE_SIZE>> {
+        None
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> LastDirections<PUZZLE_SIZE>
+    for HashMap<PuzzleState<PUZZLE_SIZE>, Option<Direction>>
+{
+    fn last_direction(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> Option<Option<Direction>> {
+        self.get(puzzle_state).copied()
+    }
+}
+
+/**
+ * Compact set of visited states with last directions.
+ * For states with several blanks, position of the last moved blank is stored separately.
+ */
+#[derive(Debug, Clone)]
+pub struct ClosedList<const PUZZLE_SIZE: usize> {
+    slots: Vec<u64>,
+    len: usize,
+    moved_blanks: HashMap<u64, BoardCoordinates<PUZZLE_SIZE>>,
+}
+
+impl<const PUZZLE_SIZE: usize> ClosedList<PUZZLE_SIZE> {
+    /// Creates empty [ClosedList].
+    pub fn new() -> Self {
+        ClosedList {
+            slots: vec![EMPTY_SLOT; INITIAL_CAPACITY],
+            len: 0,
+            moved_blanks: HashMap::new(),
+        }
+    }
+
+    /// Returns number of visited states.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if there are no visited states.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks if `puzzle_state` was visited.
+    pub fn contains(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        self.last_direction(puzzle_state).is_some()
+    }
+
+    /**
+     * Marks `puzzle_state` as visited by move in `last_direction`. Returns `false` (and keeps
+     * previous direction) if state was already visited. With several blanks, moved blank is
+     * assumed to be [PuzzleState::blank_position].
+     */
+    pub fn insert(
+        &mut self,
+        puzzle_state: PuzzleState<PUZZLE_SIZE>,
+        last_direction: Option<Direction>,
+    ) -> bool {
+        self.insert_with_moved_blank(puzzle_state, last_direction, puzzle_state.blank_position())
+    }
+
+    /**
+     * Marks `puzzle_state` as visited like [ClosedList::insert], where last move moved blank now
+     * at `moved_blank` coordinates.
+     */
+    pub fn insert_with_moved_blank(
+        &mut self,
+        puzzle_state: PuzzleState<PUZZLE_SIZE>,
+        last_direction: Option<Direction>,
+        moved_blank: BoardCoordinates<PUZZLE_SIZE>,
+    ) -> bool {
+        if (self.len + 1) * MAX_LOAD_DENOMINATOR > self.slots.len() * MAX_LOAD_NUMERATOR {
+            self.grow();
+        }
+
+        let key = Self::key(&puzzle_state);
+        let slot_index = self.find_slot(key);
+
+        if self.slots[slot_index] != EMPTY_SLOT {
+            return false;
+        }
+
+        self.slots[slot_index] = key | (direction_tag(last_direction) << Self::tag_shift());
+        self.len += 1;
+
+        if puzzle_state.blanks_count() > 1 {
+            self.moved_blanks.insert(key, moved_blank);
+        }
+
+        true
+    }
+
+    /// Returns memory used by table in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.slots.capacity() * mem::size_of::<u64>()
+            + self.moved_blanks.capacity() * mem::size_of::<(u64, BoardCoordinates<PUZZLE_SIZE>)>()
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> ClosedList<PUZZLE_SIZE> {
+    /// Tag is stored in place of number in the last board cell.
+    const fn tag_shift() -> usize {
+        TAG_WIDTH * (PUZZLE_SIZE * PUZZLE_SIZE - 1)
+    }
+
+    /// Mask of bits other than tag.
+    const fn key_mask() -> u64 {
+        !(TAG_MASK << Self::tag_shift())
+    }
+
+    /// Returns packed numbers without the last board cell.
+    fn key(puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> u64 {
+        puzzle_state.packed_numbers() & Self::key_mask()
+    }
+
+    /// Returns index of slot containing `key` or of empty slot where it should be inserted.
+    fn find_slot(&self, key: u64) -> usize {
+        let index_mask = self.slots.len() - 1;
+        let mut slot_index = hash(key, self.slots.len()) & index_mask;
+
+        loop {
+            let slot = self.slots[slot_index];
+
+            if slot == EMPTY_SLOT || slot & Self::key_mask() == key {
+                return slot_index;
+            }
+
+            slot_index = (slot_index + 1) & index_mask;
+        }
+    }
+
+    /// Doubles number of slots.
+    fn grow(&mut self) {
+        let new_slots = vec![EMPTY_SLOT; self.slots.len() * 2];
+        let old_slots = mem::replace(&mut self.slots, new_slots);
+
+        for slot in old_slots {
+            if slot != EMPTY_SLOT {
+                let slot_index = self.find_slot(slot & Self::key_mask());
+
+                self.slots[slot_index] = slot;
+            }
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> LastDirections<PUZZLE_SIZE> for ClosedList<PUZZLE_SIZE> {
+    fn last_direction(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> Option<Option<Direction>> {
+        let slot = self.slots[self.find_slot(Self::key(puzzle_state))];
+
+        if slot == EMPTY_SLOT {
+            None
+        } else {
+            Some(direction_from_tag((slot >> Self::tag_shift()) & TAG_MASK))
+        }
+    }
+
+    fn moved_blank(
+        &self,
+        puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<BoardCoordinates<PUZZLE_SIZE>> {
+        self.moved_blanks.get(&Self::key(puzzle_state)).copied()
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Default for ClosedList<PUZZLE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns index in table with `slots_count` (power of two) slots using Fibonacci hashing.
+fn hash(key: u64, slots_count: usize) -> usize {
+    (key.wrapping_mul(HASH_MULTIPLIER) >> (u64::BITS - slots_count.trailing_zeros())) as usize
+}
+
+/// Non-zero tag of direction, so that used slots are never empty.
+fn direction_tag(direction: Option<Direction>) -> u64 {
+    match direction {
+        None => 1,
+        Some(Direction::Up) => 2,
+        Some(Direction::Down) => 3,
+        Some(Direction::Left) => 4,
+        Some(Direction::Right) => 5,
+    }
+}
+
+fn direction_from_tag(tag: u64) -> Option<Direction> {
+    match tag {
+        1 => None,
+        2 => Some(Direction::Up),
+        3 => Some(Direction::Down),
+        4 => Some(Direction::Left),
+        5 => Some(Direction::Right),
+        _ => unreachable!("Only valid tags are stored in closed list."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generator::uniform_random_puzzle_state;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 4;
+
+    const SEED: u64 = 42;
+
+    #[test]
+    fn insert_and_lookup_work() {
+        let mut closed_list = ClosedList::<PUZZLE_SIZE>::new();
+        let solved_state = PuzzleState::solved();
+        let neighbour_state = solved_state.create_neighbour_move_state(Direction::Up);
+
+        assert!(closed_list.is_empty());
+        assert_eq!(None, closed_list.last_direction(&solved_state));
+
+        assert!(closed_list.insert(solved_state, None));
+        assert!(closed_list.insert(neighbour_state, Some(Direction::Up)));
+        assert!(!closed_list.insert(solved_state, Some(Direction::Left)));
+
+        assert_eq!(2, closed_list.len());
+        assert_eq!(Some(None), closed_list.last_direction(&solved_state));
+        assert_eq!(
+            Some(Some(Direction::Up)),
+            closed_list.last_direction(&neighbour_state)
+        );
+        assert!(!closed_list.contains(&solved_state.create_neighbour_move_state(Direction::Left)));
+    }
+
+    #[test]
+    fn matches_hash_map_after_growing() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let mut closed_list = ClosedList::<BIGGER_PUZZLE_SIZE>::new();
+        let mut last_directions = HashMap::new();
+        let directions = [
+            None,
+            Some(Direction::Up),
+            Some(Direction::Down),
+            Some(Direction::Left),
+            Some(Direction::Right),
+        ];
+
+        for state_index in 0..10 * INITIAL_CAPACITY {
+            let puzzle_state = uniform_random_puzzle_state(&mut rng);
+            let direction = directions[state_index % directions.len()];
+
+            assert_eq!(
+                !last_directions.contains_key(&puzzle_state),
+                closed_list.insert(puzzle_state, direction)
+            );
+
+            last_directions.entry(puzzle_state).or_insert(direction);
+        }
+
+        assert_eq!(last_directions.len(), closed_list.len());
+        // At least 3/8 of slots are used after growing.
+        assert!(closed_list.memory_usage() <= 3 * closed_list.len() * mem::size_of::<u64>());
+
+        for (puzzle_state, &direction) in &last_directions {
+            assert_eq!(Some(direction), closed_list.last_direction(puzzle_state));
+        }
+
+        for _ in 0..INITIAL_CAPACITY {
+            let puzzle_state = uniform_random_puzzle_state(&mut rng);
+
+            assert_eq!(
+                last_directions.get(&puzzle_state).copied(),
+                closed_list.last_direction(&puzzle_state)
+            );
+        }
+    }
+}
@@ -0,0 +1,398 @@
+/*!
+* Breadth-first search from solved state keeping layers on disk instead of in memory.
+*
+* Every layer is a file with sorted ranks ([PuzzleState::rank]) of states with the same distance
+* to solution, stored as little-endian `u64` values. Next layer is generated in runs of limited
+* size, which are sorted in memory and written to separate files. Runs are then merged, duplicates
+* are removed and states from the previous two layers are skipped (every neighbour of a layer
+* state lies in previous, current or next layer).
+*
+* Finished layer is renamed from temporary file at once, so search can be resumed from the last
+* finished layer after interruption. Search is complete when the last layer file is empty.
+*
+* Only square boards are searched, as [PuzzleState] is always `PUZZLE_SIZE` x `PUZZLE_SIZE`.
+* Rectangular boards (such as 2x5) would need rectangular puzzle states first, so search is
+* tested on 2x2 and 3x3 boards.
+*/
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::PuzzleState;
+
+/// Default number of states sorted in memory at once.
+const DEFAULT_RUN_SIZE: usize = 1 << 22;
+const RANK_SIZE: usize = mem::size_of::<u64>();
+const LAYER_FILE_PREFIX: &str = "layer_";
+const LAYER_FILE_EXTENSION: &str = "bin";
+const TEMPORARY_FILE_EXTENSION: &str = "tmp";
+const RUN_FILE_EXTENSION: &str = "run";
+
+/// Settings of external-memory breadth-first search.
+#[derive(Debug, Clone)]
+pub struct ExternalBfs {
+    directory: PathBuf,
+    max_distance: Option<usize>,
+    run_size: usize,
+}
+
+impl ExternalBfs {
+    /// Creates new instance of [ExternalBfs] storing layers in `directory` (created if missing).
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        ExternalBfs {
+            directory: directory.into(),
+            max_distance: None,
+            run_size: DEFAULT_RUN_SIZE,
+        }
+    }
+
+    /// Stops search after layer with `max_distance` is finished.
+    pub fn with_max_distance(self, max_distance: usize) -> Self {
+        ExternalBfs {
+            max_distance: Some(max_distance),
+            ..self
+        }
+    }
+
+    /// Sets number of states sorted in memory at once (at least 1).
+    pub fn with_run_size(self, run_size: usize) -> Self {
+        ExternalBfs {
+            run_size: run_size.max(1),
+            ..self
+        }
+    }
+
+    /// Returns path of file with states at given `distance`.
+    pub fn layer_path(&self, distance: usize) -> PathBuf {
+        self.file_path(distance, LAYER_FILE_EXTENSION)
+    }
+
+    /**
+     * Runs search (resuming from finished layers found in directory) and returns number of states
+     * in each layer. The last returned layer is empty if whole state space was searched.
+     */
+    pub fn run<const PUZZLE_SIZE: usize>(&self) -> io::Result<Vec<u64>> {
+        fs::create_dir_all(&self.directory)?;
+
+        let mut layer_sizes = self.finished_layer_sizes()?;
+
+        if layer_sizes.is_empty() {
+            let solved_rank = PuzzleState::<PUZZLE_SIZE>::solved().rank();
+
+            self.write_layer(0, |writer| write_rank(writer, solved_rank))?;
+            layer_sizes.push(1);
+        }
+
+        while layer_sizes.last() != Some(&0)
+            && self
+                .max_distance
+                .is_none_or(|max_distance| layer_sizes.len() <= max_distance)
+        {
+            let distance = layer_sizes.len();
+            let layer_size = self.generate_layer::<PUZZLE_SIZE>(distance)?;
+
+            layer_sizes.push(layer_size);
+        }
+
+        Ok(layer_sizes)
+    }
+
+    /// Reads ranks of states at given `distance` from finished layer file.
+    pub fn read_layer(&self, distance: usize) -> io::Result<Vec<u64>> {
+        let mut layer_reader = RankReader::open(&self.layer_path(distance))?;
+        let mut ranks = vec![];
+
+        while let Some(rank) = layer_reader.next_rank()? {
+            ranks.push(rank);
+        }
+
+        Ok(ranks)
+    }
+}
+
+// Private impl block
+impl ExternalBfs {
+    fn file_path(&self, distance: usize, extension: &str) -> PathBuf {
+        self.directory
+            .join(format!("{LAYER_FILE_PREFIX}{distance:03}.{extension}"))
+    }
+
+    fn run_path(&self, distance: usize, run_index: usize) -> PathBuf {
+        self.file_path(distance, &format!("{RUN_FILE_EXTENSION}{run_index}"))
+    }
+
+    /// Returns sizes of consecutive finished layers starting from distance 0.
+    fn finished_layer_sizes(&self) -> io::Result<Vec<u64>> {
+        let mut layer_sizes = vec![];
+
+        loop {
+            match fs::metadata(self.layer_path(layer_sizes.len())) {
+                Ok(metadata) => {
+                    if metadata.len() % RANK_SIZE as u64 != 0 {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Layer {} has invalid size.", layer_sizes.len()),
+                        ));
+                    }
+
+                    layer_sizes.push(metadata.len() / RANK_SIZE as u64);
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => return Ok(layer_sizes),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Writes layer to temporary file and renames it when it is complete.
+    fn write_layer(
+        &self,
+        distance: usize,
+        write_ranks: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let temporary_path = self.file_path(distance, TEMPORARY_FILE_EXTENSION);
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+
+        write_ranks(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|error| error.into_error())?
+            .sync_all()?;
+
+        fs::rename(temporary_path, self.layer_path(distance))
+    }
+
+    /// Generates layer at `distance` from two previous layers and returns its size.
+    fn generate_layer<const PUZZLE_SIZE: usize>(&self, distance: usize) -> io::Result<u64> {
+        let runs_count = self.write_runs::<PUZZLE_SIZE>(distance)?;
+        let mut layer_size = 0;
+
+        self.write_layer(distance, |writer| {
+            let mut runs = (0..runs_count)
+                .map(|run_index| RankReader::open(&self.run_path(distance, run_index)))
+                .collect::<io::Result<Vec<_>>>()?;
+            let mut previous_layer = RankReader::open(&self.layer_path(distance - 1))?;
+            let mut second_previous_layer = if distance >= 2 {
+                Some(RankReader::open(&self.layer_path(distance - 2))?)
+            } else {
+                None
+            };
+
+            let mut heap = BinaryHeap::new();
+
+            for (run_index, run) in runs.iter_mut().enumerate() {
+                if let Some(rank) = run.next_rank()? {
+                    heap.push(Reverse((rank, run_index)));
+                }
+            }
+
+            let mut last_rank = None;
+
+            while let Some(Reverse((rank, run_index))) = heap.pop() {
+                if let Some(next_rank) = runs[run_index].next_rank()? {
+                    heap.push(Reverse((next_rank, run_index)));
+                }
+
+                if last_rank == Some(rank) {
+                    continue;
+                }
+
+                last_rank = Some(rank);
+
+                let in_second_previous_layer = match &mut second_previous_layer {
+                    Some(layer) => layer.skip_to(rank)?,
+                    None => false,
+                };
+
+                if !in_second_previous_layer && !previous_layer.skip_to(rank)? {
+                    write_rank(writer, rank)?;
+                    layer_size += 1;
+                }
+            }
+
+            Ok(())
+        })?;
+
+        for run_index in 0..runs_count {
+            fs::remove_file(self.run_path(distance, run_index))?;
+        }
+
+        Ok(layer_size)
+    }
+
+    /// Writes sorted runs of neighbours of previous layer and returns number of runs.
+    fn write_runs<const PUZZLE_SIZE: usize>(&self, distance: usize) -> io::Result<usize> {
+        let mut previous_layer = RankReader::open(&self.layer_path(distance - 1))?;
+        let mut run = Vec::with_capacity(self.run_size);
+        let mut runs_count = 0;
+
+        loop {
+            let rank = previous_layer.next_rank()?;
+
+            if let Some(rank) = rank {
+                let puzzle_state = PuzzleState::<PUZZLE_SIZE>::unrank(rank).ok_or_else(|| {
+                    io::Error::new(ErrorKind::InvalidData, format!("Invalid rank {rank}."))
+                })?;
+
+                for neighbour in puzzle_state.neighbours() {
+                    let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                    run.push(neighbour_state.rank());
+                }
+            }
+
+            if run.len() + 4 > self.run_size || (rank.is_none() && !run.is_empty()) {
+                run.sort_unstable();
+                run.dedup();
+
+                let mut writer = BufWriter::new(File::create(self.run_path(distance, runs_count))?);
+
+                for &rank in &run {
+                    write_rank(&mut writer, rank)?;
+                }
+
+                writer.flush()?;
+                run.clear();
+                runs_count += 1;
+            }
+
+            if rank.is_none() {
+                return Ok(runs_count);
+            }
+        }
+    }
+}
+
+/// Sequential reader of ranks from layer or run file.
+struct RankReader {
+    reader: BufReader<File>,
+    next_rank: Option<u64>,
+}
+
+impl RankReader {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut rank_reader = RankReader {
+            reader: BufReader::new(File::open(path)?),
+            next_rank: None,
+        };
+
+        rank_reader.next_rank = rank_reader.read_rank()?;
+
+        Ok(rank_reader)
+    }
+
+    fn next_rank(&mut self) -> io::Result<Option<u64>> {
+        let rank = self.next_rank;
+
+        self.next_rank = self.read_rank()?;
+
+        Ok(rank)
+    }
+
+    /// Skips ranks lower than `rank` and checks if `rank` is in file (ranks have to be sorted).
+    fn skip_to(&mut self, rank: u64) -> io::Result<bool> {
+        while self.next_rank.is_some_and(|next_rank| next_rank < rank) {
+            self.next_rank()?;
+        }
+
+        Ok(self.next_rank == Some(rank))
+    }
+
+    fn read_rank(&mut self) -> io::Result<Option<u64>> {
+        let mut bytes = [0; RANK_SIZE];
+
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Ok(Some(u64::from_le_bytes(bytes))),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+fn write_rank(writer: &mut impl Write, rank: u64) -> io::Result<()> {
+    writer.write_all(&rank.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::PerfectHeuristic;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    /// Returns empty directory unique for test.
+    fn test_directory(test_name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "puzzle_external_bfs_{}_{test_name}",
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&directory);
+
+        directory
+    }
+
+    #[test]
+    fn small_puzzle_layers_work() {
+        let directory = test_directory("small_puzzle_layers_work");
+        let external_bfs = ExternalBfs::new(&directory).with_run_size(3);
+
+        let layer_sizes = external_bfs.run::<PUZZLE_SIZE>().unwrap();
+
+        assert_eq!(vec![1, 2, 2, 2, 2, 2, 1, 0], layer_sizes);
+
+        let perfect_heuristic = PerfectHeuristic::<PUZZLE_SIZE>::new();
+        let last_layer: Vec<_> = perfect_heuristic
+            .states_at_distance(6)
+            .iter()
+            .map(PuzzleState::rank)
+            .collect();
+
+        assert_eq!(last_layer, external_bfs.read_layer(6).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn matches_perfect_heuristic_after_resume() {
+        let directory = test_directory("matches_perfect_heuristic_after_resume");
+        let run_size = 10_000;
+
+        let interrupted_layer_sizes = ExternalBfs::new(&directory)
+            .with_run_size(run_size)
+            .with_max_distance(20)
+            .run::<BIGGER_PUZZLE_SIZE>()
+            .unwrap();
+
+        assert_eq!(21, interrupted_layer_sizes.len());
+
+        // Simulate interruption during generation of next layer.
+        File::create(directory.join("layer_021.tmp")).unwrap();
+        File::create(directory.join("layer_021.run0")).unwrap();
+
+        let external_bfs = ExternalBfs::new(&directory).with_run_size(run_size);
+        let layer_sizes = external_bfs.run::<BIGGER_PUZZLE_SIZE>().unwrap();
+
+        let expected_layer_sizes: Vec<_> = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new()
+            .distance_histogram()
+            .into_iter()
+            .map(|layer_size| layer_size as u64)
+            .chain([0])
+            .collect();
+
+        assert_eq!(expected_layer_sizes, layer_sizes);
+        assert_eq!(interrupted_layer_sizes[..], layer_sizes[..21]);
+
+        let antipodal_ranks = external_bfs.read_layer(31).unwrap();
+
+        assert_eq!(2, antipodal_ranks.len());
+        assert!(antipodal_ranks[0] < antipodal_ranks[1]);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
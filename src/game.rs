@@ -0,0 +1,211 @@
+//! Interactive sliding puzzle game played by user, with undo, redo and hints.
+
+use crate::heuristics::Heuristic;
+use crate::hint::{Hint, HintCache};
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::{Direction, PuzzleState};
+
+/**
+* Game state with history of moves. Player moves single blank, so states with several blanks
+* aren't supported.
+*/
+#[derive(Debug, Clone)]
+pub struct Game<const PUZZLE_SIZE: usize> {
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    /// Position of blank moved by player.
+    blank_position: BoardCoordinates<PUZZLE_SIZE>,
+    done_moves: Vec<Direction>,
+    undone_moves: Vec<Direction>,
+    hint_cache: HintCache<PUZZLE_SIZE>,
+}
+
+impl<const PUZZLE_SIZE: usize> Game<PUZZLE_SIZE> {
+    /// Creates new instance of [Game] starting from `puzzle_state`.
+    pub fn new(puzzle_state: PuzzleState<PUZZLE_SIZE>) -> Self {
+        Game {
+            puzzle_state,
+            blank_position: puzzle_state.blank_position(),
+            done_moves: vec![],
+            undone_moves: vec![],
+            hint_cache: HintCache::new(),
+        }
+    }
+
+    /// Accessor for `puzzle_state` field.
+    pub fn puzzle_state(&self) -> PuzzleState<PUZZLE_SIZE> {
+        self.puzzle_state
+    }
+
+    /// Accessor for `blank_position` field.
+    pub fn blank_position(&self) -> BoardCoordinates<PUZZLE_SIZE> {
+        self.blank_position
+    }
+
+    /// Returns number of moves done from initial state (undone moves are not counted).
+    pub fn moves_count(&self) -> usize {
+        self.done_moves.len()
+    }
+
+    /// Returns direction of last done move.
+    pub fn last_move(&self) -> Option<Direction> {
+        self.done_moves.last().copied()
+    }
+
+    /// Checks if game is solved.
+    pub fn is_solved(&self) -> bool {
+        self.puzzle_state.is_solved()
+    }
+
+    /**
+     * Moves blank in given `direction`. Returns `false` if blank can't move there (it is at
+     * board edge or next to obstacle or other blank).
+     * Undone moves can't be redone after new move.
+     */
+    pub fn move_blank(&mut self, direction: Direction) -> bool {
+        if !self.apply_move(direction) {
+            return false;
+        }
+
+        self.done_moves.push(direction);
+        self.undone_moves.clear();
+
+        true
+    }
+
+    /// Undoes last done move. Returns `false` if there is no move to undo.
+    pub fn undo(&mut self) -> bool {
+        if let Some(direction) = self.done_moves.pop() {
+            self.apply_move(direction.opposite());
+            self.undone_moves.push(direction);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Redoes last undone move. Returns `false` if there is no move to redo.
+    pub fn redo(&mut self) -> bool {
+        if let Some(direction) = self.undone_moves.pop() {
+            self.apply_move(direction);
+            self.done_moves.push(direction);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /**
+     * Returns hint for current state (`None` if game is solved or unsolvable), see
+     * [HintCache::hint]. Hints are cached for whole game.
+     */
+    pub fn hint(&mut self, heuristic: &dyn Heuristic<PUZZLE_SIZE>, budget: usize) -> Option<Hint> {
+        self.hint_cache.hint(self.puzzle_state, heuristic, budget)
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> Game<PUZZLE_SIZE> {
+    /// Moves blank at `blank_position` in given `direction`, returns `false` if it can't move.
+    fn apply_move(&mut self, direction: Direction) -> bool {
+        let Some(moved_state) = self
+            .puzzle_state
+            .create_blank_move_state(self.blank_position, direction)
+        else {
+            return false;
+        };
+
+        self.puzzle_state = moved_state;
+        self.blank_position = self
+            .puzzle_state
+            .topology()
+            .neighbour(self.blank_position, direction)
+            .expect("Blank has to move to board cell.");
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::ManhattanDistance;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const HINT_BUDGET: usize = 1000;
+
+    fn game() -> Game<BIGGER_PUZZLE_SIZE> {
+        Game::new(
+            PuzzleState::new([
+                [Some(1), Some(2), Some(3)],
+                [Some(4), Some(5), Some(6)],
+                [Some(7), None, Some(8)],
+            ])
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn moves_work() {
+        let mut game = game();
+
+        assert!(!game.move_blank(Direction::Down));
+        assert_eq!(0, game.moves_count());
+
+        assert!(game.move_blank(Direction::Left));
+        assert!(game.move_blank(Direction::Right));
+        assert!(game.move_blank(Direction::Right));
+
+        assert_eq!(3, game.moves_count());
+        assert_eq!(Some(Direction::Right), game.last_move());
+        assert!(game.is_solved());
+    }
+
+    #[test]
+    fn undo_and_redo_work() {
+        let mut game = game();
+        let initial_state = game.puzzle_state();
+
+        assert!(!game.undo());
+        assert!(game.move_blank(Direction::Up));
+        assert!(game.move_blank(Direction::Left));
+
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(!game.undo());
+        assert_eq!(initial_state, game.puzzle_state());
+        assert_eq!(0, game.moves_count());
+
+        assert!(game.redo());
+        assert_eq!(Some(Direction::Up), game.last_move());
+
+        assert!(game.move_blank(Direction::Down));
+        assert!(!game.redo());
+        assert_eq!(initial_state, game.puzzle_state());
+        assert_eq!(2, game.moves_count());
+    }
+
+    #[test]
+    fn hint_works() {
+        let mut game = game();
+        let manhattan_distance = ManhattanDistance::new();
+
+        assert!(game.move_blank(Direction::Up));
+
+        assert_eq!(
+            Some(Hint {
+                direction: Direction::Down,
+                remaining_moves: 2,
+                exact: true,
+            }),
+            game.hint(&manhattan_distance, HINT_BUDGET)
+        );
+
+        assert!(game.move_blank(Direction::Down));
+        assert!(game.move_blank(Direction::Right));
+
+        assert_eq!(None, game.hint(&manhattan_distance, HINT_BUDGET));
+    }
+}
@@ -1,66 +1,378 @@
-//! Generation of random initial states for sliding puzzle.
+/*!
+* Generation of random initial states for sliding puzzle.
+* All generators take random number generator, so seeded one (e.g.
+* [StdRng::seed_from_u64](rand::SeedableRng::seed_from_u64)) gives reproducible states.
+*/
 
 use rand::prelude::*;
 
-use crate::puzzle_state::coordinates::BoardCoordinates;
-use crate::{Direction, PuzzleState};
+use crate::analysis::analyze_layers;
+use crate::heuristics::Heuristic;
+use crate::puzzle_state::topology::Topology;
+use crate::{solve_with_heuristic, Direction, PuzzleState};
 
 pub const PUZZLE_SIZE: usize = 4;
 pub const SHUFFLE_ITERATIONS: usize = 1;
 
 /// Generates random 15 puzzle state to be solved by solver.
 pub fn generate_random_puzzle_state(steps_back: usize) -> PuzzleState<PUZZLE_SIZE> {
-    let mut numbers = [
-        [Some(1), Some(2), Some(3), Some(4)],
-        [Some(5), Some(6), Some(7), Some(8)],
-        [Some(9), Some(10), Some(11), Some(12)],
-        [Some(13), Some(14), Some(15), None],
-    ];
-
-    let mut last_coordinates =
-        BoardCoordinates::<PUZZLE_SIZE>::new((PUZZLE_SIZE - 1) as u8, (PUZZLE_SIZE - 1) as u8);
-    let mut last_direction = Direction::Down;
-
-    for _ in 0..steps_back {
-        let mut available_directions = vec![];
-
-        if !last_coordinates.at_upper_edge() {
-            available_directions.push(Direction::Up);
+    random_walk_puzzle_state(steps_back, &mut rand::thread_rng())
+}
+
+/**
+* Generates uniformly random solvable state on flat board. Every solvable state is equally
+* likely, so generated states are mostly hard (e.g. about 52 moves from solution for 15 puzzle).
+*/
+pub fn uniform_random_puzzle_state<const PUZZLE_SIZE: usize, R: Rng + ?Sized>(
+    rng: &mut R,
+) -> PuzzleState<PUZZLE_SIZE> {
+    let rank = rng.gen_range(0..PuzzleState::<PUZZLE_SIZE>::solvable_states_count());
+
+    PuzzleState::unrank(rank).expect("Rank is smaller than solvable states count.")
+}
+
+/**
+* Generates uniformly random solvable state on board with given `topology`. On toroidal board of
+* odd size all states are solvable, so two numbers of state from [uniform_random_puzzle_state]
+* are swapped with probability 1/2 to reach also states of other parity.
+*/
+pub fn uniform_random_puzzle_state_with_topology<const PUZZLE_SIZE: usize, R: Rng + ?Sized>(
+    topology: Topology,
+    rng: &mut R,
+) -> PuzzleState<PUZZLE_SIZE> {
+    let puzzle_state = uniform_random_puzzle_state(rng).with_topology(topology);
+
+    if !topology.wraps::<PUZZLE_SIZE>() || PUZZLE_SIZE.is_multiple_of(2) || rng.gen() {
+        return puzzle_state;
+    }
+
+    let mut numbers = puzzle_state.readable_numbers();
+    let mut number_cells = numbers
+        .iter_mut()
+        .flatten()
+        .filter(|number| number.is_some());
+
+    if let (Some(first_number), Some(second_number)) = (number_cells.next(), number_cells.next()) {
+        std::mem::swap(first_number, second_number);
+    }
+
+    PuzzleState::new(numbers)
+        .expect("Swapped numbers form valid state.")
+        .with_topology(topology)
+}
+
+/**
+* Generates state by performing exactly `steps` random moves from solved state, never undoing
+* previous move. Number of steps is an upper bound of distance to solution.
+*/
+pub fn random_walk_puzzle_state<const PUZZLE_SIZE: usize, R: Rng + ?Sized>(
+    steps: usize,
+    rng: &mut R,
+) -> PuzzleState<PUZZLE_SIZE> {
+    random_walk_from(PuzzleState::solved(), steps, rng)
+}
+
+/**
+* Generates state by performing exactly `steps` random moves from `puzzle_state` with single
+* blank, so moves follow its board topology and obstacles. Previous move is undone only in dead
+* ends, where it is the only possible move.
+*
+* # Panics
+*
+* If `puzzle_state` has several blanks.
+*/
+pub fn random_walk_from<const PUZZLE_SIZE: usize, R: Rng + ?Sized>(
+    mut puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    steps: usize,
+    rng: &mut R,
+) -> PuzzleState<PUZZLE_SIZE> {
+    assert_eq!(
+        1,
+        puzzle_state.blanks_count(),
+        "Random walk supports only states with single blank."
+    );
+
+    let mut last_direction: Option<Direction> = None;
+
+    for _ in 0..steps {
+        let mut available_moves = [(Direction::Up, puzzle_state); 4];
+        let mut available_moves_count = 0;
+        let mut undoing_move = None;
+
+        for neighbour in puzzle_state.neighbours() {
+            let (direction, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            if Some(direction.opposite()) != last_direction {
+                available_moves[available_moves_count] = (direction, neighbour_state);
+                available_moves_count += 1;
+            } else {
+                undoing_move = Some((direction, neighbour_state));
+            }
         }
 
-        if !last_coordinates.at_bottom_edge() {
-            available_directions.push(Direction::Down);
+        let Some(&(direction, neighbour_state)) = available_moves[..available_moves_count]
+            .choose(rng)
+            .or(undoing_move.as_ref())
+        else {
+            // Blank is walled in by obstacles.
+            break;
+        };
+
+        puzzle_state = neighbour_state;
+        last_direction = Some(direction);
+    }
+
+    puzzle_state
+}
+
+/**
+* Returns all states which are exactly `distance` moves from solution (sorted by
+* [PuzzleState::rank]). It uses BFS from solved state, so it is feasible only for small distances
+* (15 puzzle has about 1.6 million states 20 moves from solution).
+*/
+pub fn puzzle_states_at_distance<const PUZZLE_SIZE: usize>(
+    distance: usize,
+) -> Vec<PuzzleState<PUZZLE_SIZE>> {
+    let layer_analysis = analyze_layers::<PUZZLE_SIZE>(Some(distance));
+
+    if layer_analysis.max_distance() == distance {
+        layer_analysis.last_layer().to_vec()
+    } else {
+        vec![]
+    }
+}
+
+/**
+* Generates random state which is `distance` moves from solution. States are created by random
+* walks and solved with admissible `heuristic` until solution length matches, so solving such
+* states has to be feasible. Returns `None` if no state is found in `max_attempts` attempts
+* (e.g. when `distance` is not reachable on the board).
+*
+* Distance is exact only for consistent heuristic, A* doesn't reopen visited states, so with
+* inconsistent one it is an upper bound of distance to solution.
+*/
+pub fn sampled_puzzle_state_at_distance<const PUZZLE_SIZE: usize, R: Rng + ?Sized>(
+    distance: usize,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    max_attempts: usize,
+    rng: &mut R,
+) -> Option<PuzzleState<PUZZLE_SIZE>> {
+    // Distance has the same parity as number of steps, so steps are changed by two.
+    let mut steps = distance;
+
+    for _ in 0..max_attempts {
+        let puzzle_state = random_walk_puzzle_state(steps, rng);
+        let solution = solve_with_heuristic(puzzle_state, heuristic)
+            .expect("Generated puzzle state has to be solvable.");
+        let solution_length = solution.steps().len();
+
+        if solution_length == distance {
+            return Some(puzzle_state);
+        } else if solution_length < distance {
+            steps += 2;
+        } else {
+            steps = (steps - 2).max(distance);
         }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if !last_coordinates.at_left_edge() {
-            available_directions.push(Direction::Left);
+    use std::collections::HashSet;
+
+    use crate::heuristics::validate::exact_distances;
+    use crate::heuristics::ManhattanDistance;
+    use crate::puzzle_state::coordinates::BoardCoordinates;
+    use crate::puzzle_state::obstacles::{Obstacles, Solvability};
+
+    const SMALL_PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    const SEED: u64 = 42;
+    const MAX_ATTEMPTS: usize = 100;
+
+    #[test]
+    fn seeded_generators_are_reproducible() {
+        let first_state: PuzzleState<PUZZLE_SIZE> =
+            uniform_random_puzzle_state(&mut StdRng::seed_from_u64(SEED));
+        let second_state = uniform_random_puzzle_state(&mut StdRng::seed_from_u64(SEED));
+
+        assert_eq!(first_state, second_state);
+
+        let first_state: PuzzleState<PUZZLE_SIZE> =
+            random_walk_puzzle_state(50, &mut StdRng::seed_from_u64(SEED));
+        let second_state = random_walk_puzzle_state(50, &mut StdRng::seed_from_u64(SEED));
+
+        assert_eq!(first_state, second_state);
+    }
+
+    #[test]
+    fn uniform_generator_covers_all_states() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let generated_states: HashSet<_> = (0..1000)
+            .map(|_| uniform_random_puzzle_state::<SMALL_PUZZLE_SIZE, _>(&mut rng))
+            .collect();
+
+        assert_eq!(12, generated_states.len());
+
+        for _ in 0..100 {
+            assert!(uniform_random_puzzle_state::<PUZZLE_SIZE, _>(&mut rng).is_solvable());
         }
+    }
+
+    #[test]
+    fn odd_toroidal_generator_covers_both_parities() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let generated_states: Vec<_> = (0..100)
+            .map(|_| {
+                uniform_random_puzzle_state_with_topology::<BIGGER_PUZZLE_SIZE, _>(
+                    Topology::Toroidal,
+                    &mut rng,
+                )
+            })
+            .collect();
+
+        assert!(generated_states
+            .iter()
+            .all(|puzzle_state| puzzle_state.topology() == Topology::Toroidal));
+        assert!(generated_states
+            .iter()
+            .any(|puzzle_state| puzzle_state.with_topology(Topology::Flat).is_solvable()));
+        assert!(generated_states
+            .iter()
+            .any(|puzzle_state| !puzzle_state.with_topology(Topology::Flat).is_solvable()));
+
+        // Flat board gets the same states as without topology.
+        assert_eq!(
+            uniform_random_puzzle_state::<BIGGER_PUZZLE_SIZE, _>(&mut StdRng::seed_from_u64(SEED)),
+            uniform_random_puzzle_state_with_topology(
+                Topology::Flat,
+                &mut StdRng::seed_from_u64(SEED)
+            )
+        );
+    }
+
+    #[test]
+    fn random_walk_does_not_backtrack() {
+        let distances = exact_distances::<BIGGER_PUZZLE_SIZE>();
+        let mut rng = StdRng::seed_from_u64(SEED);
 
-        if !last_coordinates.at_right_edge() {
-            available_directions.push(Direction::Right);
+        for steps in 0..=3 {
+            for _ in 0..20 {
+                let puzzle_state =
+                    random_walk_puzzle_state::<BIGGER_PUZZLE_SIZE, _>(steps, &mut rng);
+
+                assert_eq!(steps as u8, distances[&puzzle_state]);
+            }
+        }
+    }
+
+    #[test]
+    fn states_at_distance_are_exact() {
+        let distances = exact_distances::<BIGGER_PUZZLE_SIZE>();
+
+        for distance in [0, 1, 5, 12] {
+            let puzzle_states = puzzle_states_at_distance::<BIGGER_PUZZLE_SIZE>(distance);
+            let expected_count = distances
+                .values()
+                .filter(|&&exact_distance| exact_distance as usize == distance)
+                .count();
+
+            assert_eq!(expected_count, puzzle_states.len());
+
+            for puzzle_state in puzzle_states {
+                assert_eq!(distance as u8, distances[&puzzle_state]);
+            }
         }
 
-        available_directions.shuffle(&mut rand::thread_rng());
+        assert_eq!(
+            vec![PuzzleState::<SMALL_PUZZLE_SIZE>::unrank(0).unwrap()],
+            puzzle_states_at_distance::<SMALL_PUZZLE_SIZE>(0)
+        );
+        assert!(puzzle_states_at_distance::<SMALL_PUZZLE_SIZE>(7).is_empty());
+    }
+
+    #[test]
+    fn sampled_states_at_distance_are_exact() {
+        let distances = exact_distances::<BIGGER_PUZZLE_SIZE>();
+        let manhattan_distance = ManhattanDistance::new();
+        let mut rng = StdRng::seed_from_u64(SEED);
 
-        let mut direction = available_directions[0];
+        for distance in [10, 20, 25] {
+            let puzzle_state = sampled_puzzle_state_at_distance::<BIGGER_PUZZLE_SIZE, _>(
+                distance,
+                &manhattan_distance,
+                MAX_ATTEMPTS,
+                &mut rng,
+            )
+            .unwrap();
 
-        if direction == last_direction {
-            direction = available_directions[1];
+            assert_eq!(distance as u8, distances[&puzzle_state]);
         }
 
-        let (row, column) = last_coordinates.as_tuple();
-        let (diff_row, diff_column) = direction.as_coordinates();
+        // The hardest 8 puzzle states are 31 moves from solution.
+        assert_eq!(
+            None,
+            sampled_puzzle_state_at_distance::<BIGGER_PUZZLE_SIZE, _>(
+                33,
+                &manhattan_distance,
+                MAX_ATTEMPTS,
+                &mut rng,
+            )
+        );
+    }
 
-        let swap_row = ((row as isize) + diff_row) as u8;
-        let swap_column = ((column as isize) + diff_column) as u8;
+    #[test]
+    fn random_walk_respects_obstacles() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let obstacles = Obstacles::<BIGGER_PUZZLE_SIZE>::new(&[
+            BoardCoordinates::new(0, 0),
+            BoardCoordinates::new(1, 1),
+        ])
+        .unwrap();
 
-        let tmp = numbers[row as usize][column as usize];
-        numbers[row as usize][column as usize] = numbers[swap_row as usize][swap_column as usize];
-        numbers[swap_row as usize][swap_column as usize] = tmp;
+        for _ in 0..10 {
+            let puzzle_state = random_walk_from(
+                PuzzleState::solved().with_obstacles(obstacles),
+                50,
+                &mut rng,
+            );
 
-        last_coordinates = BoardCoordinates::new(swap_row, swap_column);
-        last_direction = direction.opposite();
+            assert_eq!(Some(1), puzzle_state.number_at(BoardCoordinates::new(0, 0)));
+            assert_eq!(Some(5), puzzle_state.number_at(BoardCoordinates::new(1, 1)));
+            assert_eq!(Solvability::Solvable, puzzle_state.solvability());
+        }
     }
 
-    PuzzleState::new(numbers).expect("Numbers have to be correct permutation after shuffle")
+    #[test]
+    fn random_walk_wraps_on_toroidal_board() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let generated_states: HashSet<_> = (0..100)
+            .map(|_| {
+                random_walk_from::<BIGGER_PUZZLE_SIZE, _>(
+                    PuzzleState::solved().with_topology(Topology::Toroidal),
+                    1,
+                    &mut rng,
+                )
+            })
+            .collect();
+
+        // Blank in corner has 4 neighbours, 2 of them across board edges.
+        assert_eq!(4, generated_states.len());
+
+        for _ in 0..10 {
+            let puzzle_state = random_walk_from::<PUZZLE_SIZE, _>(
+                PuzzleState::solved().with_topology(Topology::Toroidal),
+                50,
+                &mut rng,
+            );
+
+            assert_eq!(Topology::Toroidal, puzzle_state.topology());
+            assert!(puzzle_state.is_solvable());
+        }
+    }
 }
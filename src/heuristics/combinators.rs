@@ -0,0 +1,273 @@
+//! Heuristics combining values of other heuristics.
+
+use std::cell::RefCell;
+
+use super::Heuristic;
+use crate::puzzle_state::PuzzleState;
+
+/**
+* Statistics gathered by combinators. For each component it counts evaluations in which
+* that component returned the biggest value (all components with equal biggest value are counted).
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentStatistics {
+    evaluations: usize,
+    maximum_counts: Vec<usize>,
+}
+
+impl ComponentStatistics {
+    /// Creates empty statistics for `components_count` components.
+    fn new(components_count: usize) -> Self {
+        ComponentStatistics {
+            evaluations: 0,
+            maximum_counts: vec![0; components_count],
+        }
+    }
+
+    /// Records component values from one evaluation.
+    fn record(&mut self, values: &[u8]) {
+        self.evaluations += 1;
+
+        if let Some(maximum) = values.iter().max() {
+            for (maximum_count, value) in self.maximum_counts.iter_mut().zip(values) {
+                if value == maximum {
+                    *maximum_count += 1;
+                }
+            }
+        }
+    }
+
+    /// Accessor for `evaluations` field.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// Accessor for `maximum_counts` field.
+    pub fn maximum_counts(&self) -> &[usize] {
+        &self.maximum_counts
+    }
+}
+
+/// Components shared by combinators with optional statistics.
+struct Components<const PUZZLE_SIZE: usize> {
+    heuristics: Vec<Box<dyn Heuristic<PUZZLE_SIZE>>>,
+    statistics: Option<RefCell<ComponentStatistics>>,
+}
+
+impl<const PUZZLE_SIZE: usize> Components<PUZZLE_SIZE> {
+    /// Creates new instance of [Components] without statistics.
+    fn new(heuristics: Vec<Box<dyn Heuristic<PUZZLE_SIZE>>>) -> Self {
+        Components {
+            heuristics,
+            statistics: None,
+        }
+    }
+
+    /// Enables or disables collecting statistics.
+    fn with_statistics(mut self, collect_statistics: bool) -> Self {
+        self.statistics = collect_statistics
+            .then(|| RefCell::new(ComponentStatistics::new(self.heuristics.len())));
+        self
+    }
+
+    /// Returns copy of collected statistics.
+    fn statistics(&self) -> Option<ComponentStatistics> {
+        self.statistics
+            .as_ref()
+            .map(|statistics| statistics.borrow().clone())
+    }
+
+    /// Checks if all components support `puzzle_state`.
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        self.heuristics
+            .iter()
+            .all(|heuristic| heuristic.supports(puzzle_state))
+    }
+
+    /// Calculates values of all components and records them in statistics.
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> Vec<u8> {
+        let values: Vec<_> = self
+            .heuristics
+            .iter()
+            .map(|heuristic| heuristic.calculate(numbers))
+            .collect();
+
+        if let Some(statistics) = &self.statistics {
+            statistics.borrow_mut().record(&values);
+        }
+
+        values
+    }
+}
+
+/**
+* Heuristic returning maximum of its components values.
+* It is admissible if all of its components are admissible.
+*/
+pub struct MaxHeuristic<const PUZZLE_SIZE: usize> {
+    components: Components<PUZZLE_SIZE>,
+}
+
+impl<const PUZZLE_SIZE: usize> MaxHeuristic<PUZZLE_SIZE> {
+    /// Creates new instance of [MaxHeuristic] without statistics.
+    pub fn new(components: Vec<Box<dyn Heuristic<PUZZLE_SIZE>>>) -> Self {
+        MaxHeuristic {
+            components: Components::new(components),
+        }
+    }
+
+    /// Enables or disables collecting per component statistics.
+    pub fn with_statistics(self, collect_statistics: bool) -> Self {
+        MaxHeuristic {
+            components: self.components.with_statistics(collect_statistics),
+        }
+    }
+
+    /// Returns collected statistics or `None` if they are disabled.
+    pub fn statistics(&self) -> Option<ComponentStatistics> {
+        self.components.statistics()
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for MaxHeuristic<PUZZLE_SIZE> {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        let values = self.components.calculate(numbers);
+
+        values.into_iter().max().unwrap_or(0)
+    }
+
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        self.components.supports(puzzle_state)
+    }
+}
+
+/**
+* Heuristic returning sum of its components values.
+* It is admissible only if components count moves of disjoint sets of numbers (like
+* [DisjointDatabases](super::DisjointDatabases) databases do).
+*/
+pub struct AdditiveHeuristic<const PUZZLE_SIZE: usize> {
+    components: Components<PUZZLE_SIZE>,
+}
+
+impl<const PUZZLE_SIZE: usize> AdditiveHeuristic<PUZZLE_SIZE> {
+    /// Creates new instance of [AdditiveHeuristic] without statistics.
+    pub fn new(components: Vec<Box<dyn Heuristic<PUZZLE_SIZE>>>) -> Self {
+        AdditiveHeuristic {
+            components: Components::new(components),
+        }
+    }
+
+    /// Enables or disables collecting per component statistics.
+    pub fn with_statistics(self, collect_statistics: bool) -> Self {
+        AdditiveHeuristic {
+            components: self.components.with_statistics(collect_statistics),
+        }
+    }
+
+    /// Returns collected statistics or `None` if they are disabled.
+    pub fn statistics(&self) -> Option<ComponentStatistics> {
+        self.components.statistics()
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for AdditiveHeuristic<PUZZLE_SIZE> {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        let values = self.components.calculate(numbers);
+
+        values
+            .into_iter()
+            .fold(0, |sum, value| sum.saturating_add(value))
+    }
+
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        self.components.supports(puzzle_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::dumb_heuristic::DumbHeuristic;
+    use crate::heuristics::manhattan_distance::ManhattanDistance;
+    use crate::puzzle_state::PuzzleState;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    fn puzzle_state() -> PuzzleState<BIGGER_PUZZLE_SIZE> {
+        PuzzleState::new([
+            [Some(1), Some(4), Some(2)],
+            [Some(3), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn max_heuristic_works() {
+        let max_heuristic = MaxHeuristic::new(vec![
+            Box::new(ManhattanDistance::new()),
+            Box::new(DumbHeuristic),
+        ]);
+
+        let heuristic_value = puzzle_state().calculate_heuristic(&max_heuristic);
+
+        assert_eq!(36, heuristic_value);
+        assert_eq!(None, max_heuristic.statistics());
+    }
+
+    #[test]
+    fn max_heuristic_statistics_work() {
+        let max_heuristic = MaxHeuristic::new(vec![
+            Box::new(ManhattanDistance::new()),
+            Box::new(DumbHeuristic),
+            Box::new(DumbHeuristic),
+        ])
+        .with_statistics(true);
+
+        puzzle_state().calculate_heuristic(&max_heuristic);
+        puzzle_state().calculate_heuristic(&max_heuristic);
+
+        let statistics = max_heuristic.statistics().unwrap();
+
+        assert_eq!(2, statistics.evaluations());
+        assert_eq!(&[0, 2, 2], statistics.maximum_counts());
+    }
+
+    #[test]
+    fn empty_max_heuristic() {
+        let max_heuristic = MaxHeuristic::new(vec![]);
+
+        assert_eq!(0, puzzle_state().calculate_heuristic(&max_heuristic));
+    }
+
+    #[test]
+    fn additive_heuristic_works() {
+        let additive_heuristic = AdditiveHeuristic::new(vec![
+            Box::new(ManhattanDistance::new()),
+            Box::new(DumbHeuristic),
+        ])
+        .with_statistics(true);
+
+        let heuristic_value = puzzle_state().calculate_heuristic(&additive_heuristic);
+
+        assert_eq!(12 + 36, heuristic_value);
+
+        let statistics = additive_heuristic.statistics().unwrap();
+
+        assert_eq!(1, statistics.evaluations());
+        assert_eq!(&[0, 1], statistics.maximum_counts());
+    }
+
+    #[test]
+    fn additive_heuristic_saturates() {
+        let additive_heuristic: AdditiveHeuristic<BIGGER_PUZZLE_SIZE> =
+            AdditiveHeuristic::new((0..8).map(|_| Box::new(DumbHeuristic) as _).collect());
+
+        assert_eq!(
+            u8::MAX,
+            puzzle_state().calculate_heuristic(&additive_heuristic)
+        );
+    }
+}
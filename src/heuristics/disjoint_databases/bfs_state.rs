@@ -20,9 +20,8 @@ impl BFSState {
 
         let mut elements_coordinates = [BoardCoordinates::<PUZZLE_SIZE>::new(0, 0); PUZZLE_SIZE];
 
-        for element_column in 0..PUZZLE_SIZE {
-            elements_coordinates[element_column] =
-                BoardCoordinates::new(elements_row as u8, element_column as u8);
+        for (element_column, element_coordinates) in elements_coordinates.iter_mut().enumerate() {
+            *element_coordinates = BoardCoordinates::new(elements_row as u8, element_column as u8);
         }
 
         let blank_coordinates =
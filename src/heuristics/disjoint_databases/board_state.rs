@@ -94,8 +94,8 @@ impl BoardState {
                 }
 
                 let (row, column) = element_coords.as_tuple();
-                let new_row = ((row as isize) + (diff_row * -1)) as u8;
-                let new_column = ((column as isize) + (diff_column * -1)) as u8;
+                let new_row = ((row as isize) + -diff_row) as u8;
+                let new_column = ((column as isize) + -diff_column) as u8;
 
                 *element_coords = BoardCoordinates::new(new_row, new_column);
                 moved_element = true;
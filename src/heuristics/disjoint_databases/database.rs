@@ -30,9 +30,7 @@ impl Database {
             let curr_bfs_state = frontier.pop().expect("Frontier can't be empty").0;
             let (combination, distance) = curr_bfs_state.combination_and_distance();
 
-            if !distances.contains_key(&combination) {
-                distances.insert(combination, distance);
-            }
+            distances.entry(combination).or_insert(distance);
 
             let neighbours = curr_bfs_state.neighbours();
 
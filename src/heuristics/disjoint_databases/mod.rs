@@ -1,9 +1,10 @@
-//! Disjoint databases heuristic (works only for 15 puzzle).
+//! Disjoint databases heuristic (works only for 15 puzzle with single blank).
 
 pub mod bfs_state;
 pub mod board_state;
 pub mod combination;
 pub mod database;
+pub mod reflection;
 
 use std::fs::File;
 
@@ -11,23 +12,29 @@ use serde::{Deserialize, Serialize};
 
 use crate::heuristics::Heuristic;
 use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::puzzle_state::PuzzleState;
 
 use combination::Combination;
 use database::Database;
+use reflection::Reflection;
 
 pub const DATABASE_SIZE: usize = 4;
 pub const DATABASES_COUNT: usize = 4;
 pub const PUZZLE_SIZE: usize = 4;
 
-const DATABASE_PATH: &'static str = "15_puzzle_heuristic_database.data";
+const DATABASE_PATH: &str = "15_puzzle_heuristic_database.data";
 
 /**
 * Disjoint databases heurstic works by splitting problem into many subproblems and calculating
 * distances for each one of them.
+* Optionally it also looks up databases for board reflected about main diagonal and returns
+* maximum of both values.
 */
 #[derive(Deserialize, Serialize)]
 pub struct DisjointDatabases {
     databases: Vec<Database>,
+    #[serde(skip)]
+    reflection: Option<Reflection>,
 }
 
 impl DisjointDatabases {
@@ -52,6 +59,12 @@ impl DisjointDatabases {
         }
     }
 
+    /// Enables or disables reflected databases lookups.
+    pub fn with_reflection(mut self, use_reflection: bool) -> DisjointDatabases {
+        self.reflection = use_reflection.then(Reflection::new);
+        self
+    }
+
     /// Creates new instance of [DisjointDatabases] and tries to save it to disk.
     fn create_fresh_instance() -> DisjointDatabases {
         let mut databases = vec![];
@@ -63,7 +76,10 @@ impl DisjointDatabases {
             databases.push(Database::new(database_first_element_index, ignore_last));
         }
 
-        let disjoint_databases = DisjointDatabases { databases };
+        let disjoint_databases = DisjointDatabases {
+            databases,
+            reflection: None,
+        };
 
         let database_file = File::create(DATABASE_PATH);
 
@@ -73,10 +89,9 @@ impl DisjointDatabases {
 
         disjoint_databases
     }
-}
 
-impl Heuristic<PUZZLE_SIZE> for DisjointDatabases {
-    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+    /// Sums distances looked up in each database for given `numbers`.
+    fn databases_distance(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
         let mut numbers_representation = [
             [BoardCoordinates::<PUZZLE_SIZE>::new(0, 0); DATABASE_SIZE],
             [BoardCoordinates::<PUZZLE_SIZE>::new(0, 0); DATABASE_SIZE],
@@ -113,6 +128,89 @@ impl Heuristic<PUZZLE_SIZE> for DisjointDatabases {
 
         distance
     }
+
+    /// Returns coordinates of all numbers from database with `database_index` index.
+    fn database_numbers_coordinates(
+        puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+        database_index: usize,
+    ) -> [BoardCoordinates<PUZZLE_SIZE>; DATABASE_SIZE] {
+        let mut numbers_coordinates = [BoardCoordinates::<PUZZLE_SIZE>::new(0, 0); DATABASE_SIZE];
+        let numbers_count = PUZZLE_SIZE * PUZZLE_SIZE - 1;
+
+        for (element_index, number_coordinates) in numbers_coordinates.iter_mut().enumerate() {
+            let number_value = database_index * DATABASE_SIZE + element_index + 1;
+
+            // Last database doesn't contain blank
+            if number_value <= numbers_count {
+                *number_coordinates = puzzle_state.number_coordinates(Some(number_value as u8));
+            }
+        }
+
+        numbers_coordinates
+    }
+}
+
+/// States with several blanks aren't in databases, they get trivially admissible value 0.
+impl Heuristic<PUZZLE_SIZE> for DisjointDatabases {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        let blanks_count = numbers
+            .iter()
+            .flatten()
+            .filter(|number| number.is_none())
+            .count();
+
+        if blanks_count > 1 {
+            return 0;
+        }
+
+        let distance = self.databases_distance(numbers);
+
+        if let Some(reflection) = &self.reflection {
+            let reflected_distance = self.databases_distance(&reflection.reflect(numbers));
+
+            distance.max(reflected_distance)
+        } else {
+            distance
+        }
+    }
+
+    /**
+     * Only database containing `moved_number` is looked up again (not supported with reflection
+     * or several blanks).
+     */
+    fn calculate_delta(
+        &self,
+        previous_value: u8,
+        moved_number: u8,
+        from: BoardCoordinates<PUZZLE_SIZE>,
+        _to: BoardCoordinates<PUZZLE_SIZE>,
+        puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<u8> {
+        if self.reflection.is_some() || puzzle_state.blanks_count() > 1 {
+            return None;
+        }
+
+        let number_index = (moved_number - 1) as usize;
+        let database_index = number_index / DATABASE_SIZE;
+        let curr_database = &self.databases[database_index];
+        let ignore_last = database_index == (DATABASE_SIZE - 1);
+
+        let mut numbers_coordinates =
+            Self::database_numbers_coordinates(puzzle_state, database_index);
+        let combination = Combination::from_readable(numbers_coordinates, ignore_last);
+
+        numbers_coordinates[number_index % DATABASE_SIZE] = from;
+        let previous_combination = Combination::from_readable(numbers_coordinates, ignore_last);
+
+        let distance = curr_database
+            .get_distance(&combination)
+            .expect("Database has to contain distance for this combination.");
+        let previous_distance = curr_database
+            .get_distance(&previous_combination)
+            .expect("Database has to contain distance for this combination.");
+
+        Some(previous_value + distance - previous_distance)
+    }
 }
 
 #[cfg(test)]
@@ -206,4 +304,111 @@ mod tests {
 
         assert_eq!(3, heuristic_value);
     }
+
+    #[test]
+    fn delta_matches_full_calculation() {
+        let disjoint_databases = DisjointDatabases::new(false);
+
+        let puzzle_states = [
+            "[2, 3, 11, 1, 5, 4, 13, 7, 15, 14, , 6, 10, 9, 12, 8]",
+            "[7, 6, 4, 1, 15, , 3, 14, 9, 2, 12, 13, 5, 10, 11, 8]",
+            "[5, 4, 10, 7, 2, 1, 12, 6, 3, 13, 15, 11, 9, 14, 8, ]",
+        ];
+
+        for puzzle_state in puzzle_states {
+            let puzzle_state = puzzle_state
+                .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+                .unwrap();
+            let heuristic_value = puzzle_state.calculate_heuristic(&disjoint_databases);
+
+            for neighbour in puzzle_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                let previous_blank = puzzle_state.blank_position();
+                let moved_number = neighbour_state.number_at(previous_blank).unwrap();
+
+                let delta_value = disjoint_databases.calculate_delta(
+                    heuristic_value,
+                    moved_number,
+                    neighbour_state.blank_position(),
+                    previous_blank,
+                    &neighbour_state,
+                );
+
+                assert_eq!(
+                    Some(neighbour_state.calculate_heuristic(&disjoint_databases)),
+                    delta_value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn delta_not_supported_with_reflection() {
+        let disjoint_databases = DisjointDatabases::new(false).with_reflection(true);
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3), Some(4)],
+            [Some(5), Some(6), Some(7), Some(8)],
+            [Some(9), Some(10), Some(11), Some(12)],
+            [Some(13), Some(14), None, Some(15)],
+        ])
+        .unwrap();
+
+        let delta_value = disjoint_databases.calculate_delta(
+            1,
+            15,
+            BoardCoordinates::new(3, 3),
+            BoardCoordinates::new(3, 2),
+            &puzzle_state,
+        );
+
+        assert_eq!(None, delta_value);
+    }
+
+    #[test]
+    fn several_blanks_are_not_supported() {
+        let disjoint_databases = DisjointDatabases::new(false).with_reflection(true);
+        let puzzle_state = "[1, 2, 3, 4, 5, 6, , 8, 9, 10, 7, 12, 13, 11, , 14]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let blank_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::solved_with_blanks(16);
+
+        assert!(!disjoint_databases.supports(&puzzle_state));
+        assert_eq!(0, puzzle_state.calculate_heuristic(&disjoint_databases));
+        assert_eq!(0, blank_state.calculate_heuristic(&disjoint_databases));
+    }
+
+    #[test]
+    fn heuristic_with_reflection_works() {
+        let disjoint_databases = DisjointDatabases::new(false).with_reflection(true);
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3), Some(4)],
+            [Some(5), Some(6), Some(7), Some(8)],
+            [Some(9), Some(10), Some(11), Some(12)],
+            [Some(13), Some(14), Some(15), None],
+        ])
+        .unwrap();
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&disjoint_databases);
+
+        assert_eq!(0, heuristic_value);
+
+        // Swapped numbers share column, so only reflected lookup sees they have to pass each other.
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(5), Some(2), Some(3), Some(4)],
+            [Some(1), Some(6), Some(7), Some(8)],
+            [Some(9), Some(10), Some(11), Some(12)],
+            [Some(13), Some(14), Some(15), None],
+        ])
+        .unwrap();
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&disjoint_databases);
+        let direct_heuristic_value =
+            puzzle_state.calculate_heuristic(&disjoint_databases.with_reflection(false));
+
+        assert_eq!(6, heuristic_value);
+        assert_eq!(4, direct_heuristic_value);
+    }
 }
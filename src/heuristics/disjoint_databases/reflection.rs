@@ -0,0 +1,122 @@
+//! Reflection of 15 puzzle board about its main diagonal.
+
+use super::PUZZLE_SIZE;
+use crate::puzzle_state::coordinates::BoardCoordinates;
+
+const NUMBERS_COUNT: usize = PUZZLE_SIZE * PUZZLE_SIZE - 1;
+
+/**
+* Solved board is symmetric about its main diagonal (blank stays in place), so board reflected
+* about that diagonal with relabelled numbers is the same distance from solution as original one.
+* It lets databases created for rows be used for columns.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Reflection {
+    relabelled_numbers: [u8; NUMBERS_COUNT],
+}
+
+impl Reflection {
+    /// Creates new instance of [Reflection] with precalculated relabelling map.
+    pub fn new() -> Reflection {
+        let mut relabelled_numbers = [0; NUMBERS_COUNT];
+
+        for (number_index, relabelled_number) in relabelled_numbers.iter_mut().enumerate() {
+            let solved_coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(
+                (number_index / PUZZLE_SIZE) as u8,
+                (number_index % PUZZLE_SIZE) as u8,
+            );
+            let (row, column) = solved_coordinates.transposed().as_tuple();
+
+            *relabelled_number = row * (PUZZLE_SIZE as u8) + column + 1;
+        }
+
+        Reflection { relabelled_numbers }
+    }
+
+    /// Returns number occupying reflected solved position of `number`.
+    pub fn relabel(&self, number: u8) -> u8 {
+        self.relabelled_numbers[(number - 1) as usize]
+    }
+
+    /// Reflects `numbers` about main board diagonal and relabels them.
+    pub fn reflect(
+        &self,
+        numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE],
+    ) -> [[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE] {
+        let mut reflected_numbers = [[None; PUZZLE_SIZE]; PUZZLE_SIZE];
+
+        for (row, numbers_row) in numbers.iter().enumerate() {
+            for (column, number) in numbers_row.iter().enumerate() {
+                let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(row as u8, column as u8);
+                let (reflected_row, reflected_column) = coordinates.transposed().as_tuple();
+
+                reflected_numbers[reflected_row as usize][reflected_column as usize] =
+                    number.map(|number_value| self.relabel(number_value));
+            }
+        }
+
+        reflected_numbers
+    }
+}
+
+impl Default for Reflection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relabelling_works() {
+        let reflection = Reflection::new();
+
+        assert_eq!(1, reflection.relabel(1));
+        assert_eq!(5, reflection.relabel(2));
+        assert_eq!(13, reflection.relabel(4));
+        assert_eq!(4, reflection.relabel(13));
+        assert_eq!(11, reflection.relabel(11));
+        assert_eq!(12, reflection.relabel(15));
+    }
+
+    #[test]
+    fn reflecting_solved_keeps_it_solved() {
+        let reflection = Reflection::new();
+
+        let numbers = [
+            [Some(1), Some(2), Some(3), Some(4)],
+            [Some(5), Some(6), Some(7), Some(8)],
+            [Some(9), Some(10), Some(11), Some(12)],
+            [Some(13), Some(14), Some(15), None],
+        ];
+
+        assert_eq!(numbers, reflection.reflect(&numbers));
+    }
+
+    #[test]
+    fn reflecting_works() {
+        let reflection = Reflection::new();
+
+        let numbers = [
+            [Some(5), Some(1), Some(3), Some(4)],
+            [None, Some(2), Some(7), Some(8)],
+            [Some(9), Some(6), Some(11), Some(12)],
+            [Some(13), Some(10), Some(14), Some(15)],
+        ];
+
+        let reflected_numbers = reflection.reflect(&numbers);
+
+        assert_eq!(
+            [
+                [Some(2), None, Some(3), Some(4)],
+                [Some(1), Some(5), Some(6), Some(7)],
+                [Some(9), Some(10), Some(11), Some(8)],
+                [Some(13), Some(14), Some(15), Some(12)],
+            ],
+            reflected_numbers
+        );
+        assert_eq!(numbers, reflection.reflect(&reflected_numbers));
+    }
+}
@@ -10,10 +10,8 @@ impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for DumbHeuristic {
         let mut heuristic_value = 0;
 
         for numbers_row in numbers {
-            for number in numbers_row {
-                if let Some(number_value) = number {
-                    heuristic_value += number_value;
-                }
+            for number_value in numbers_row.iter().flatten() {
+                heuristic_value += number_value;
             }
         }
 
@@ -4,14 +4,19 @@ use std::collections::HashMap;
 
 use super::Heuristic;
 use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::puzzle_state::topology::Topology;
+use crate::puzzle_state::PuzzleState;
 
 /**
  * [Manhattan Distance](https://en.wikipedia.org/wiki/Taxicab_geometry) heuristic.
  * It calculates manhattan distance of each number from its proper position and sums those
  * distances up.
+ * Blanks are ignored, so it is admissible also for states with several blanks.
+ * On toroidal board distances wrap around board edges.
  */
 pub struct ManhattanDistance<const PUZZLE_SIZE: usize> {
     solved_positions: HashMap<Option<u8>, BoardCoordinates<PUZZLE_SIZE>>,
+    topology: Topology,
 }
 
 impl<const PUZZLE_SIZE: usize> ManhattanDistance<PUZZLE_SIZE> {
@@ -22,7 +27,15 @@ impl<const PUZZLE_SIZE: usize> ManhattanDistance<PUZZLE_SIZE> {
     pub fn new() -> Self {
         let solved_positions = Self::create_solved_positions();
 
-        ManhattanDistance { solved_positions }
+        ManhattanDistance {
+            solved_positions,
+            topology: Topology::Flat,
+        }
+    }
+
+    /// Sets board `topology` used to measure distances.
+    pub fn with_topology(self, topology: Topology) -> Self {
+        ManhattanDistance { topology, ..self }
     }
 
     /// Returns number coordinates in solved sliding puzzle game.
@@ -56,6 +69,12 @@ impl<const PUZZLE_SIZE: usize> ManhattanDistance<PUZZLE_SIZE> {
     }
 }
 
+impl<const PUZZLE_SIZE: usize> Default for ManhattanDistance<PUZZLE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for ManhattanDistance<PUZZLE_SIZE> {
     fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
         let mut distance = 0;
@@ -70,22 +89,48 @@ impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for ManhattanDistance<PUZZ
                     let number_actual_coordinates =
                         BoardCoordinates::<PUZZLE_SIZE>::new(row as u8, column as u8);
 
-                    distance +=
-                        number_actual_coordinates.manhattan_distance(number_solved_coordinates);
+                    distance += self
+                        .topology
+                        .distance(&number_actual_coordinates, number_solved_coordinates);
                 }
             }
         }
 
         distance
     }
+
+    fn calculate_delta(
+        &self,
+        previous_value: u8,
+        moved_number: u8,
+        from: BoardCoordinates<PUZZLE_SIZE>,
+        to: BoardCoordinates<PUZZLE_SIZE>,
+        _puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<u8> {
+        let number_solved_coordinates = self
+            .solved_positions
+            .get(&Some(moved_number))
+            .expect("ManhattanDistance has to have all number distances cached.");
+
+        let value = previous_value + self.topology.distance(&to, number_solved_coordinates)
+            - self.topology.distance(&from, number_solved_coordinates);
+
+        Some(value)
+    }
+
+    /**
+     * Blanks are ignored, so states with several blanks are supported. Flat distances can
+     * overestimate on toroidal board, so topology has to match.
+     */
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        puzzle_state.topology() == self.topology
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::puzzle_state::PuzzleState;
-
     const BIGGER_PUZZLE_SIZE: usize = 3;
 
     #[test]
@@ -114,4 +159,76 @@ mod tests {
 
         assert_eq!(10, heuristic_value);
     }
+
+    #[test]
+    fn delta_matches_full_calculation() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(4), Some(2)],
+            [Some(3), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&manhattan_distance);
+
+        for neighbour in puzzle_state.neighbours() {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            let previous_blank = puzzle_state.blank_position();
+            let moved_number = neighbour_state.number_at(previous_blank).unwrap();
+
+            let delta_value = manhattan_distance.calculate_delta(
+                heuristic_value,
+                moved_number,
+                neighbour_state.blank_position(),
+                previous_blank,
+                &neighbour_state,
+            );
+
+            assert_eq!(
+                Some(neighbour_state.calculate_heuristic(&manhattan_distance)),
+                delta_value
+            );
+        }
+    }
+
+    #[test]
+    fn toroidal_distances_wrap() {
+        let manhattan_distance = ManhattanDistance::new().with_topology(Topology::Toroidal);
+
+        // Every number is one wrapping move from its position.
+        let puzzle_state = "[3, 1, 2, 6, 4, 5, , 7, 8]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_topology(Topology::Toroidal);
+
+        assert_eq!(8, puzzle_state.calculate_heuristic(&manhattan_distance));
+        assert_eq!(
+            10,
+            puzzle_state.calculate_heuristic(&ManhattanDistance::new())
+        );
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&manhattan_distance);
+
+        assert_eq!(4, puzzle_state.neighbours().len());
+
+        for neighbour in puzzle_state.neighbours() {
+            let (direction, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+            let previous_blank = Topology::Toroidal
+                .neighbour(neighbour_state.blank_position(), direction.opposite())
+                .unwrap();
+
+            assert_eq!(
+                neighbour_state.calculate_heuristic(&manhattan_distance),
+                neighbour_state.calculate_heuristic_after_move(
+                    &manhattan_distance,
+                    heuristic_value,
+                    neighbour_state.blank_position(),
+                    previous_blank
+                )
+            );
+        }
+    }
 }
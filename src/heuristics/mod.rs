@@ -1,14 +1,54 @@
 //! Heuristics for sliding puzzle A* solver.
 
+pub mod combinators;
 pub mod disjoint_databases;
 pub mod manhattan_distance;
+pub mod obstacle_distance;
+pub mod partial_manhattan_distance;
 pub mod dumb_heuristic;
+pub mod perfect_heuristic;
+pub mod spec;
+pub mod validate;
 
+pub use combinators::{AdditiveHeuristic, MaxHeuristic};
 pub use manhattan_distance::ManhattanDistance;
+pub use obstacle_distance::ObstacleDistance;
+pub use partial_manhattan_distance::PartialManhattanDistance;
 pub use disjoint_databases::DisjointDatabases;
+pub use perfect_heuristic::PerfectHeuristic;
+pub use spec::HeuristicSpec;
+
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::puzzle_state::PuzzleState;
 
 /// Trait for declaring different heuristics.
 pub trait Heuristic<const PUZZLE_SIZE: usize> {
     /// Function that calculates heuristic value for given numbers in puzzle state.
     fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8;
+
+    /**
+     * Function that calculates heuristic value for `puzzle_state` obtained by moving
+     * `moved_number` `from` coordinates `to` coordinates, where `previous_value` is heuristic
+     * value before that move.
+     * Returns `None` if heuristic can't calculate it without looking at whole board (default).
+     */
+    fn calculate_delta(
+        &self,
+        _previous_value: u8,
+        _moved_number: u8,
+        _from: BoardCoordinates<PUZZLE_SIZE>,
+        _to: BoardCoordinates<PUZZLE_SIZE>,
+        _puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<u8> {
+        None
+    }
+
+    /**
+     * Checks if heuristic is admissible for `puzzle_state` (its number of blanks and board),
+     * search fails with [SearchError::UnsupportedHeuristic](crate::SearchError) otherwise.
+     * By default only states with single blank on board which doesn't wrap are supported.
+     */
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        puzzle_state.blanks_count() == 1 && !puzzle_state.topology().wraps::<PUZZLE_SIZE>()
+    }
 }
@@ -0,0 +1,161 @@
+//! Shortest path distance heuristic for boards with obstacles.
+
+use super::Heuristic;
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::puzzle_state::obstacles::Obstacles;
+use crate::puzzle_state::topology::Topology;
+use crate::puzzle_state::PuzzleState;
+
+/**
+ * Replacement of [ManhattanDistance](super::ManhattanDistance) for boards with [Obstacles].
+ * It sums shortest path distances of numbers from their solved positions going around obstacles
+ * (numbers which can't reach their positions count as 0, such states are unsolvable anyway).
+ * Without obstacles it equals manhattan distance. It supports only states on board with the
+ * same obstacles and [Topology].
+ */
+pub struct ObstacleDistance<const PUZZLE_SIZE: usize> {
+    /// Distance to solved position for every number (first index) and cell (second index).
+    distances: Vec<Vec<u8>>,
+    obstacles: Obstacles<PUZZLE_SIZE>,
+    topology: Topology,
+}
+
+impl<const PUZZLE_SIZE: usize> ObstacleDistance<PUZZLE_SIZE> {
+    /**
+     * Creates new instance of [ObstacleDistance] with precalculated distances around `obstacles`
+     * on flat board.
+     */
+    pub fn new(obstacles: &Obstacles<PUZZLE_SIZE>) -> Self {
+        Self::with_board(*obstacles, Topology::Flat)
+    }
+
+    /// Sets board `topology` used to measure distances (distances are recalculated).
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self::with_board(self.obstacles, topology)
+    }
+
+    fn with_board(obstacles: Obstacles<PUZZLE_SIZE>, topology: Topology) -> Self {
+        let cells_count = PUZZLE_SIZE * PUZZLE_SIZE;
+        let cell_distances = obstacles.cell_distances(topology);
+        let mut distances = vec![vec![0; cells_count]; cells_count];
+
+        // Number `n` belongs to cell `n - 1`.
+        for number_value in 1..cells_count {
+            for (cell_index, distance) in distances[number_value].iter_mut().enumerate() {
+                *distance = cell_distances[number_value - 1][cell_index].unwrap_or_default();
+            }
+        }
+
+        ObstacleDistance {
+            distances,
+            obstacles,
+            topology,
+        }
+    }
+
+    fn distance(&self, number: u8, coordinates: BoardCoordinates<PUZZLE_SIZE>) -> u8 {
+        let (row, column) = coordinates.as_tuple();
+
+        self.distances[number as usize][row as usize * PUZZLE_SIZE + column as usize]
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for ObstacleDistance<PUZZLE_SIZE> {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        let mut distance = 0;
+
+        for (row, number_row) in numbers.iter().enumerate() {
+            for (column, number) in number_row.iter().enumerate() {
+                if let Some(number_value) = number {
+                    distance += self.distance(
+                        *number_value,
+                        BoardCoordinates::new(row as u8, column as u8),
+                    );
+                }
+            }
+        }
+
+        distance
+    }
+
+    fn calculate_delta(
+        &self,
+        previous_value: u8,
+        moved_number: u8,
+        from: BoardCoordinates<PUZZLE_SIZE>,
+        to: BoardCoordinates<PUZZLE_SIZE>,
+        _puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<u8> {
+        Some(previous_value + self.distance(moved_number, to) - self.distance(moved_number, from))
+    }
+
+    /**
+     * Every move shifts one number by one cell, no matter how many blanks there are, but
+     * distances have to be measured on the same board.
+     */
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        puzzle_state.obstacles() == self.obstacles && puzzle_state.topology() == self.topology
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generator::uniform_random_puzzle_state;
+    use crate::heuristics::ManhattanDistance;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const BIGGEST_PUZZLE_SIZE: usize = 4;
+
+    const SEED: u64 = 42;
+
+    #[test]
+    fn equals_manhattan_distance_without_obstacles() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let obstacle_distance = ObstacleDistance::new(&Obstacles::new(&[]).unwrap());
+        let manhattan_distance = ManhattanDistance::new();
+
+        for _ in 0..100 {
+            let puzzle_state = uniform_random_puzzle_state::<BIGGEST_PUZZLE_SIZE, _>(&mut rng);
+
+            assert_eq!(
+                puzzle_state.calculate_heuristic(&manhattan_distance),
+                puzzle_state.calculate_heuristic(&obstacle_distance)
+            );
+        }
+    }
+
+    #[test]
+    fn distances_go_around_obstacles() {
+        let obstacles =
+            Obstacles::<BIGGER_PUZZLE_SIZE>::new(&[BoardCoordinates::new(1, 1)]).unwrap();
+        let obstacle_distance = ObstacleDistance::new(&obstacles);
+
+        // 2 and 8 are 4 moves from their positions around blocked centre, 5 is in place.
+        let puzzle_state = "[1, 8, 3, 4, 5, 6, 7, 2, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+
+        assert_eq!(8, puzzle_state.calculate_heuristic(&obstacle_distance));
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&obstacle_distance);
+
+        for neighbour in puzzle_state.neighbours() {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            assert_eq!(
+                neighbour_state.calculate_heuristic(&obstacle_distance),
+                neighbour_state.calculate_heuristic_after_move(
+                    &obstacle_distance,
+                    heuristic_value,
+                    neighbour_state.blank_position(),
+                    puzzle_state.blank_position()
+                )
+            );
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Manhattan Distance heuristic restricted to tiles of [PartialGoal].
+
+use super::Heuristic;
+use crate::partial_goal::PartialGoal;
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::puzzle_state::PuzzleState;
+
+/**
+ * Sum of manhattan distances of tiles required by [PartialGoal] from their target cells.
+ * Other tiles (and blank) are ignored, so heuristic is admissible and consistent for that goal,
+ * also when some cells are locked.
+ */
+pub struct PartialManhattanDistance<const PUZZLE_SIZE: usize> {
+    /// Target cell for every number (indexed by number), `None` if number isn't required.
+    target_positions: Vec<Option<BoardCoordinates<PUZZLE_SIZE>>>,
+}
+
+impl<const PUZZLE_SIZE: usize> PartialManhattanDistance<PUZZLE_SIZE> {
+    /// Creates new instance of [PartialManhattanDistance] for `partial_goal`.
+    pub fn new(partial_goal: &PartialGoal<PUZZLE_SIZE>) -> Self {
+        let mut target_positions = vec![None; PUZZLE_SIZE * PUZZLE_SIZE];
+
+        for &(number, coordinates) in partial_goal.targets() {
+            if let Some(number_value) = number {
+                target_positions[number_value as usize] = Some(coordinates);
+            }
+        }
+
+        PartialManhattanDistance { target_positions }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for PartialManhattanDistance<PUZZLE_SIZE> {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        let mut distance = 0;
+
+        for (row, number_row) in numbers.iter().enumerate() {
+            for (column, number) in number_row.iter().enumerate() {
+                if let Some(target_coordinates) =
+                    number.and_then(|number_value| self.target_positions[number_value as usize])
+                {
+                    distance += BoardCoordinates::<PUZZLE_SIZE>::new(row as u8, column as u8)
+                        .manhattan_distance(&target_coordinates);
+                }
+            }
+        }
+
+        distance
+    }
+
+    fn calculate_delta(
+        &self,
+        previous_value: u8,
+        moved_number: u8,
+        from: BoardCoordinates<PUZZLE_SIZE>,
+        to: BoardCoordinates<PUZZLE_SIZE>,
+        _puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    ) -> Option<u8> {
+        let value = match self.target_positions[moved_number as usize] {
+            Some(target_coordinates) => {
+                previous_value + to.manhattan_distance(&target_coordinates)
+                    - from.manhattan_distance(&target_coordinates)
+            }
+            None => previous_value,
+        };
+
+        Some(value)
+    }
+
+    /// Only goal tiles are counted, so any number of blanks is supported (on flat board).
+    fn supports(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        !puzzle_state.topology().wraps::<PUZZLE_SIZE>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::ManhattanDistance;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn heuristic_works() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(4), Some(2)],
+            [Some(3), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        // Only tiles 2 (distance 1) and 4 (distance 2) are counted.
+        let partial_goal = PartialGoal::from_tiles(&[1, 2, 4]).unwrap();
+
+        assert_eq!(
+            3,
+            puzzle_state.calculate_heuristic(&PartialManhattanDistance::new(&partial_goal))
+        );
+
+        let full_goal = PartialGoal::from_tiles(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+        assert_eq!(
+            puzzle_state.calculate_heuristic(&ManhattanDistance::new()),
+            puzzle_state.calculate_heuristic(&PartialManhattanDistance::new(&full_goal))
+        );
+    }
+
+    #[test]
+    fn delta_matches_full_calculation() {
+        let partial_goal = "[*, 4, *, 3, *, *, *, *, 8]"
+            .parse::<PartialGoal<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let heuristic = PartialManhattanDistance::new(&partial_goal);
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(4), Some(2)],
+            [Some(3), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        let heuristic_value = puzzle_state.calculate_heuristic(&heuristic);
+
+        for neighbour in puzzle_state.neighbours() {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            assert_eq!(
+                neighbour_state.calculate_heuristic(&heuristic),
+                neighbour_state.calculate_heuristic_after_move(
+                    &heuristic,
+                    heuristic_value,
+                    neighbour_state.blank_position(),
+                    puzzle_state.blank_position()
+                )
+            );
+        }
+    }
+}
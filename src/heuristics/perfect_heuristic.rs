@@ -0,0 +1,236 @@
+/*!
+* Perfect heuristic which knows exact distance to solution for each state.
+* Distances for all states are precalculated, so it is feasible only for puzzles up to size 3
+* (8 puzzle has 9! / 2 = 181 440 solvable states).
+*/
+
+use std::collections::VecDeque;
+
+use super::Heuristic;
+use crate::puzzle_state::PuzzleState;
+
+const UNKNOWN_DISTANCE: u8 = u8::MAX;
+
+/// Biggest puzzle size for which distances of all states fit in memory.
+const MAX_PUZZLE_SIZE: usize = 3;
+
+/**
+* Heuristic returning exact distances to solution precalculated with BFS from solved state.
+* Distances are indexed by [PuzzleState::rank], so only states with single blank are supported.
+*/
+pub struct PerfectHeuristic<const PUZZLE_SIZE: usize> {
+    distances: Vec<u8>,
+}
+
+impl<const PUZZLE_SIZE: usize> PerfectHeuristic<PUZZLE_SIZE> {
+    /**
+     * Creates new instance of [PerfectHeuristic] calculating distances for all states.
+     *
+     * # Panics
+     *
+     * If `PUZZLE_SIZE` is greater than 3.
+     */
+    pub fn new() -> Self {
+        assert!(
+            PUZZLE_SIZE <= MAX_PUZZLE_SIZE,
+            "Perfect heuristic supports puzzle size up to {MAX_PUZZLE_SIZE}, got: {PUZZLE_SIZE}"
+        );
+
+        let states_count = PuzzleState::<PUZZLE_SIZE>::solvable_states_count() as usize;
+        let mut distances = vec![UNKNOWN_DISTANCE; states_count];
+        let mut frontier = VecDeque::new();
+
+        let solved_state = PuzzleState::<PUZZLE_SIZE>::solved();
+
+        distances[solved_state.rank() as usize] = 0;
+        frontier.push_back(solved_state);
+
+        while let Some(puzzle_state) = frontier.pop_front() {
+            let distance = distances[puzzle_state.rank() as usize];
+
+            for neighbour in puzzle_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+                let neighbour_rank = neighbour_state.rank() as usize;
+
+                if distances[neighbour_rank] == UNKNOWN_DISTANCE {
+                    distances[neighbour_rank] = distance + 1;
+                    frontier.push_back(neighbour_state);
+                }
+            }
+        }
+
+        PerfectHeuristic { distances }
+    }
+
+    /**
+     * Returns exact distance to solution or `None` if `puzzle_state` is not solvable or has
+     * several blanks.
+     */
+    pub fn distance(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> Option<u8> {
+        PuzzleState::rank_readable_numbers(&puzzle_state.readable_numbers())
+            .map(|rank| self.distances[rank as usize])
+    }
+
+    /// Returns all states which are exactly `distance` moves from solution (sorted by rank).
+    pub fn states_at_distance(&self, distance: u8) -> Vec<PuzzleState<PUZZLE_SIZE>> {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter(|&(_, &state_distance)| state_distance == distance)
+            .map(|(rank, _)| {
+                PuzzleState::unrank(rank as u64).expect("Distances are indexed by state rank.")
+            })
+            .collect()
+    }
+
+    /// Returns number of solvable states for each distance to solution.
+    pub fn distance_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![];
+
+        for &distance in &self.distances {
+            if histogram.len() <= distance as usize {
+                histogram.resize(distance as usize + 1, 0);
+            }
+
+            histogram[distance as usize] += 1;
+        }
+
+        histogram
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Default for PerfectHeuristic<PUZZLE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+* States which can't be ranked (unsolvable ones or with several blanks) get trivially admissible
+* value 0.
+*/
+impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for PerfectHeuristic<PUZZLE_SIZE> {
+    fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+        PuzzleState::rank_readable_numbers(numbers).map_or(0, |rank| self.distances[rank as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::validate::exact_distances;
+    use crate::heuristics::ManhattanDistance;
+    use crate::solve_with_heuristic;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn distance_histogram_works() {
+        let perfect_heuristic = PerfectHeuristic::<PUZZLE_SIZE>::new();
+
+        assert_eq!(
+            vec![1, 2, 2, 2, 2, 2, 1],
+            perfect_heuristic.distance_histogram()
+        );
+
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+        let distance_histogram = perfect_heuristic.distance_histogram();
+
+        assert_eq!(32, distance_histogram.len());
+        assert_eq!(2, distance_histogram[31]);
+        assert_eq!(181_440, distance_histogram.iter().sum::<usize>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_big_puzzle_is_rejected() {
+        let _perfect_heuristic = PerfectHeuristic::<4>::new();
+    }
+
+    #[test]
+    fn states_at_distance_work() {
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+        let hardest_states = perfect_heuristic.states_at_distance(31);
+
+        assert_eq!(2, hardest_states.len());
+
+        for puzzle_state in hardest_states {
+            assert_eq!(Some(31), perfect_heuristic.distance(&puzzle_state));
+        }
+
+        assert_eq!(
+            vec![PuzzleState::solved()],
+            perfect_heuristic.states_at_distance(0)
+        );
+        assert!(perfect_heuristic.states_at_distance(32).is_empty());
+    }
+
+    #[test]
+    fn distances_are_exact() {
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+
+        for (puzzle_state, distance) in exact_distances::<BIGGER_PUZZLE_SIZE>() {
+            assert_eq!(Some(distance), perfect_heuristic.distance(&puzzle_state));
+            assert_eq!(
+                distance,
+                puzzle_state.calculate_heuristic(&perfect_heuristic)
+            );
+        }
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), Some(5), Some(6)],
+            [Some(8), Some(7), None],
+        ])
+        .unwrap();
+        let several_blanks_state = "[1, 2, 3, 4, , 6, 7, 5, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+
+        assert_eq!(None, perfect_heuristic.distance(&puzzle_state));
+        assert_eq!(0, puzzle_state.calculate_heuristic(&perfect_heuristic));
+        assert_eq!(None, perfect_heuristic.distance(&several_blanks_state));
+        assert_eq!(
+            0,
+            several_blanks_state.calculate_heuristic(&perfect_heuristic)
+        );
+        assert!(!perfect_heuristic.supports(&several_blanks_state));
+    }
+
+    #[test]
+    fn astar_expands_only_solution_path() {
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [None, Some(2), Some(3)],
+            [Some(1), Some(5), Some(6)],
+            [Some(4), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        let solution = solve_with_heuristic(puzzle_state, &perfect_heuristic).unwrap();
+
+        assert_eq!(4, solution.steps().len());
+        assert_eq!(solution.steps().len() + 1, solution.no_of_visited_states());
+    }
+
+    #[test]
+    fn solver_finds_optimal_solutions() {
+        let perfect_heuristic = PerfectHeuristic::<BIGGER_PUZZLE_SIZE>::new();
+        let manhattan_distance = ManhattanDistance::<BIGGER_PUZZLE_SIZE>::new();
+
+        let hard_puzzle_states = exact_distances::<BIGGER_PUZZLE_SIZE>()
+            .into_iter()
+            .filter(|&(_, distance)| distance >= 24)
+            .take(5);
+
+        for (puzzle_state, distance) in hard_puzzle_states {
+            let solution = solve_with_heuristic(puzzle_state, &manhattan_distance).unwrap();
+
+            assert_eq!(distance as usize, solution.steps().len());
+            assert_eq!(Some(distance), perfect_heuristic.distance(&puzzle_state));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Textual description of heuristics, e.g. `max(manhattan,pdb)`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::{AdditiveHeuristic, Heuristic, MaxHeuristic};
+
+/// Heuristics which can be named in [HeuristicSpec].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeuristicName {
+    /// [ManhattanDistance](super::ManhattanDistance), named `manhattan` or `manhattan-distance`.
+    ManhattanDistance,
+    /// [DisjointDatabases](super::DisjointDatabases), named `pdb` or `disjoint-databases`.
+    DisjointDatabases,
+    /// [PerfectHeuristic](super::PerfectHeuristic), named `perfect`.
+    Perfect,
+}
+
+impl HeuristicName {
+    /// Finds heuristic by one of its names.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "manhattan" | "manhattan-distance" => Some(HeuristicName::ManhattanDistance),
+            "pdb" | "disjoint-databases" => Some(HeuristicName::DisjointDatabases),
+            "perfect" => Some(HeuristicName::Perfect),
+            _ => None,
+        }
+    }
+}
+
+/// Description of heuristic composed from named heuristics and combinators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeuristicSpec {
+    /// Single named heuristic.
+    Named(HeuristicName),
+    /// [MaxHeuristic] over components, written as `max(a,b,...)`.
+    Max(Vec<HeuristicSpec>),
+    /// [AdditiveHeuristic] over components, written as `sum(a,b,...)`.
+    Sum(Vec<HeuristicSpec>),
+}
+
+impl HeuristicSpec {
+    /**
+     * Creates heuristic described by spec. Named heuristics are created by `create_named`, so
+     * caller decides which of them are available for given puzzle size.
+     */
+    pub fn build<const PUZZLE_SIZE: usize, E>(
+        &self,
+        create_named: &mut impl FnMut(HeuristicName) -> Result<Box<dyn Heuristic<PUZZLE_SIZE>>, E>,
+    ) -> Result<Box<dyn Heuristic<PUZZLE_SIZE>>, E> {
+        match self {
+            HeuristicSpec::Named(heuristic_name) => create_named(*heuristic_name),
+            HeuristicSpec::Max(components) => Ok(Box::new(MaxHeuristic::new(
+                Self::build_components(components, create_named)?,
+            ))),
+            HeuristicSpec::Sum(components) => Ok(Box::new(AdditiveHeuristic::new(
+                Self::build_components(components, create_named)?,
+            ))),
+        }
+    }
+
+    /// Creates heuristics for each of combinator components.
+    fn build_components<const PUZZLE_SIZE: usize, E>(
+        components: &[HeuristicSpec],
+        create_named: &mut impl FnMut(HeuristicName) -> Result<Box<dyn Heuristic<PUZZLE_SIZE>>, E>,
+    ) -> Result<Vec<Box<dyn Heuristic<PUZZLE_SIZE>>>, E> {
+        components
+            .iter()
+            .map(|component| component.build(create_named))
+            .collect()
+    }
+
+    /// Parses spec starting at the beginning of `s` and returns it with remaining input.
+    fn parse_prefix(s: &str) -> Result<(HeuristicSpec, &str), HeuristicSpecParseError> {
+        let s = s.trim_start();
+        let name_end = s.find(['(', ')', ',']).unwrap_or(s.len());
+        let name = s[..name_end].trim();
+        let rest = &s[name_end..];
+
+        if let Some(arguments) = rest.strip_prefix('(') {
+            let mut components = vec![];
+            let mut rest = arguments;
+
+            loop {
+                let (component, component_rest) = Self::parse_prefix(rest)?;
+                components.push(component);
+
+                let component_rest = component_rest.trim_start();
+
+                if let Some(next_component) = component_rest.strip_prefix(',') {
+                    rest = next_component;
+                } else if let Some(after_arguments) = component_rest.strip_prefix(')') {
+                    rest = after_arguments;
+                    break;
+                } else {
+                    return Err(HeuristicSpecParseError::UnclosedParenthesis);
+                }
+            }
+
+            let spec = match name {
+                "max" => HeuristicSpec::Max(components),
+                "sum" => HeuristicSpec::Sum(components),
+                _ => return Err(HeuristicSpecParseError::UnknownCombinator(name.to_string())),
+            };
+
+            Ok((spec, rest))
+        } else {
+            let heuristic_name = HeuristicName::from_name(name)
+                .ok_or_else(|| HeuristicSpecParseError::UnknownHeuristic(name.to_string()))?;
+
+            Ok((HeuristicSpec::Named(heuristic_name), rest))
+        }
+    }
+}
+
+impl FromStr for HeuristicSpec {
+    type Err = HeuristicSpecParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (spec, rest) = Self::parse_prefix(s)?;
+
+        if rest.trim().is_empty() {
+            Ok(spec)
+        } else {
+            Err(HeuristicSpecParseError::UnexpectedInput(rest.to_string()))
+        }
+    }
+}
+
+/// Errors that can occur when parsing [HeuristicSpec].
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeuristicSpecParseError {
+    /// Heuristic with given name doesn't exist.
+    UnknownHeuristic(String),
+    /// Combinator with given name doesn't exist.
+    UnknownCombinator(String),
+    /// Combinator arguments are not closed with parenthesis.
+    UnclosedParenthesis,
+    /// There is input left after parsed spec.
+    UnexpectedInput(String),
+}
+
+impl Display for HeuristicSpecParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeuristicSpecParseError::UnknownHeuristic(name) => {
+                write!(f, "unknown heuristic: '{name}'")
+            }
+            HeuristicSpecParseError::UnknownCombinator(name) => {
+                write!(f, "unknown combinator: '{name}' (expected max or sum)")
+            }
+            HeuristicSpecParseError::UnclosedParenthesis => write!(f, "unclosed parenthesis"),
+            HeuristicSpecParseError::UnexpectedInput(input) => {
+                write!(f, "unexpected input: '{input}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeuristicSpecParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::dumb_heuristic::DumbHeuristic;
+    use crate::heuristics::ManhattanDistance;
+    use crate::puzzle_state::PuzzleState;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn parse_named() {
+        assert_eq!(
+            Ok(HeuristicSpec::Named(HeuristicName::ManhattanDistance)),
+            "manhattan".parse()
+        );
+
+        assert_eq!(
+            Ok(HeuristicSpec::Named(HeuristicName::DisjointDatabases)),
+            " disjoint-databases ".parse()
+        );
+    }
+
+    #[test]
+    fn parse_combinators() {
+        assert_eq!(
+            Ok(HeuristicSpec::Max(vec![
+                HeuristicSpec::Named(HeuristicName::ManhattanDistance),
+                HeuristicSpec::Sum(vec![
+                    HeuristicSpec::Named(HeuristicName::DisjointDatabases),
+                    HeuristicSpec::Named(HeuristicName::ManhattanDistance),
+                ]),
+            ])),
+            "max(manhattan, sum(pdb,manhattan-distance))".parse()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Err(HeuristicSpecParseError::UnknownHeuristic(
+                "euclid".to_string()
+            )),
+            "max(euclid,pdb)".parse::<HeuristicSpec>()
+        );
+
+        assert_eq!(
+            Err(HeuristicSpecParseError::UnknownCombinator(
+                "min".to_string()
+            )),
+            "min(manhattan,pdb)".parse::<HeuristicSpec>()
+        );
+
+        assert_eq!(
+            Err(HeuristicSpecParseError::UnclosedParenthesis),
+            "max(manhattan,pdb".parse::<HeuristicSpec>()
+        );
+
+        assert_eq!(
+            Err(HeuristicSpecParseError::UnexpectedInput(")".to_string())),
+            "max(manhattan,pdb))".parse::<HeuristicSpec>()
+        );
+    }
+
+    #[test]
+    fn build_works() {
+        let spec: HeuristicSpec = "sum(manhattan,max(manhattan,pdb))".parse().unwrap();
+
+        let heuristic = spec
+            .build::<BIGGER_PUZZLE_SIZE, ()>(&mut |heuristic_name| match heuristic_name {
+                HeuristicName::ManhattanDistance => Ok(Box::new(ManhattanDistance::new())),
+                HeuristicName::DisjointDatabases | HeuristicName::Perfect => {
+                    Ok(Box::new(DumbHeuristic))
+                }
+            })
+            .unwrap();
+
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(4), Some(2)],
+            [Some(3), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        assert_eq!(12 + 36, puzzle_state.calculate_heuristic(&*heuristic));
+    }
+
+    #[test]
+    fn build_propagates_errors() {
+        let spec: HeuristicSpec = "max(manhattan,pdb)".parse().unwrap();
+
+        let heuristic =
+            spec.build::<BIGGER_PUZZLE_SIZE, _>(&mut |heuristic_name| match heuristic_name {
+                HeuristicName::ManhattanDistance => Ok(Box::new(ManhattanDistance::new())),
+                HeuristicName::DisjointDatabases | HeuristicName::Perfect => Err(heuristic_name),
+            });
+
+        assert!(matches!(heuristic, Err(HeuristicName::DisjointDatabases)));
+    }
+}
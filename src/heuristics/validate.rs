@@ -0,0 +1,272 @@
+/*!
+* Tools for checking if heuristic is admissible (never overestimates distance to solution) and
+* consistent (changes by at most one between neighbouring states).
+* Small boards are checked exhaustively, bigger ones on sampled states.
+*
+* Only square boards (2x2, 3x3 and sampled 4x4) are checked, as [PuzzleState] is always
+* `PUZZLE_SIZE` x `PUZZLE_SIZE`. Rectangular boards such as 2x3 would need rectangular puzzle
+* states first.
+*/
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::Rng;
+
+use super::Heuristic;
+use crate::generator::random_walk_puzzle_state;
+use crate::{solve_with_heuristic, PuzzleState, DEFAULT_PUZZLE_SIZE};
+
+/// State for which heuristic overestimates distance to solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InadmissibleState<const PUZZLE_SIZE: usize> {
+    /// Checked state.
+    pub puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    /// Heuristic value for checked state.
+    pub heuristic_value: u8,
+    /// Exact distance from checked state to solution.
+    pub distance: u8,
+}
+
+/// Move between neighbouring states for which heuristic value changes by more than one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InconsistentMove<const PUZZLE_SIZE: usize> {
+    /// State before move.
+    pub from: PuzzleState<PUZZLE_SIZE>,
+    /// State after move.
+    pub to: PuzzleState<PUZZLE_SIZE>,
+    /// Heuristic value before move.
+    pub from_heuristic_value: u8,
+    /// Heuristic value after move.
+    pub to_heuristic_value: u8,
+}
+
+/// Results of heuristic validation.
+#[derive(Debug, Clone)]
+pub struct ValidationReport<const PUZZLE_SIZE: usize> {
+    checked_states: usize,
+    inadmissible_states: Vec<InadmissibleState<PUZZLE_SIZE>>,
+    inconsistent_moves: Vec<InconsistentMove<PUZZLE_SIZE>>,
+    ratios_sum: f64,
+    ratios_count: usize,
+}
+
+impl<const PUZZLE_SIZE: usize> ValidationReport<PUZZLE_SIZE> {
+    /// Creates empty report.
+    fn new() -> Self {
+        ValidationReport {
+            checked_states: 0,
+            inadmissible_states: vec![],
+            inconsistent_moves: vec![],
+            ratios_sum: 0.0,
+            ratios_count: 0,
+        }
+    }
+
+    /**
+     * Checks `puzzle_state` which is `distance` moves from solution. Its moves are checked only
+     * in direction in which heuristic value decreases, so each inconsistent move is reported once
+     * when all states are checked.
+     */
+    fn check_state(
+        &mut self,
+        puzzle_state: PuzzleState<PUZZLE_SIZE>,
+        distance: u8,
+        heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    ) {
+        let heuristic_value = puzzle_state.calculate_heuristic(heuristic);
+
+        self.checked_states += 1;
+
+        if heuristic_value > distance {
+            self.inadmissible_states.push(InadmissibleState {
+                puzzle_state,
+                heuristic_value,
+                distance,
+            });
+        }
+
+        if distance > 0 {
+            self.ratios_sum += (heuristic_value as f64) / (distance as f64);
+            self.ratios_count += 1;
+        }
+
+        for neighbour in puzzle_state.neighbours() {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+            let neighbour_heuristic_value = neighbour_state.calculate_heuristic(heuristic);
+
+            if heuristic_value > neighbour_heuristic_value + 1 {
+                self.inconsistent_moves.push(InconsistentMove {
+                    from: puzzle_state,
+                    to: neighbour_state,
+                    from_heuristic_value: heuristic_value,
+                    to_heuristic_value: neighbour_heuristic_value,
+                });
+            }
+        }
+    }
+
+    /// Accessor for `checked_states` field.
+    pub fn checked_states(&self) -> usize {
+        self.checked_states
+    }
+
+    /// Accessor for `inadmissible_states` field.
+    pub fn inadmissible_states(&self) -> &[InadmissibleState<PUZZLE_SIZE>] {
+        &self.inadmissible_states
+    }
+
+    /// Accessor for `inconsistent_moves` field.
+    pub fn inconsistent_moves(&self) -> &[InconsistentMove<PUZZLE_SIZE>] {
+        &self.inconsistent_moves
+    }
+
+    /// Returns average ratio of heuristic value to exact distance (solved states are skipped).
+    pub fn average_ratio(&self) -> Option<f64> {
+        (self.ratios_count > 0).then(|| self.ratios_sum / (self.ratios_count as f64))
+    }
+}
+
+/**
+* Calculates exact distances to solution for all solvable states using BFS from solved state.
+* It is feasible only for small boards (3x3 board has 181 440 solvable states).
+*/
+pub fn exact_distances<const PUZZLE_SIZE: usize>() -> HashMap<PuzzleState<PUZZLE_SIZE>, u8> {
+    let solved_state = PuzzleState::solved();
+
+    let mut distances = HashMap::from([(solved_state, 0)]);
+    let mut frontier = VecDeque::from([solved_state]);
+
+    while let Some(puzzle_state) = frontier.pop_front() {
+        let distance = distances[&puzzle_state];
+
+        for neighbour in puzzle_state.neighbours() {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            distances.entry(neighbour_state).or_insert_with(|| {
+                frontier.push_back(neighbour_state);
+                distance + 1
+            });
+        }
+    }
+
+    distances
+}
+
+/// Validates `heuristic` on all solvable states of small board.
+pub fn validate_exhaustive<const PUZZLE_SIZE: usize>(
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+) -> ValidationReport<PUZZLE_SIZE> {
+    let mut report = ValidationReport::new();
+
+    for (puzzle_state, distance) in exact_distances::<PUZZLE_SIZE>() {
+        report.check_state(puzzle_state, distance, heuristic);
+    }
+
+    report
+}
+
+/**
+* Validates `heuristic` on `samples` states created with [random_walk_puzzle_state] from `rng`.
+* Exact distances are found by solving states with `reference` heuristic, which has to be
+* admissible.
+*/
+pub fn validate_sampled<R: Rng + ?Sized>(
+    heuristic: &dyn Heuristic<DEFAULT_PUZZLE_SIZE>,
+    reference: &dyn Heuristic<DEFAULT_PUZZLE_SIZE>,
+    samples: usize,
+    steps_back: usize,
+    rng: &mut R,
+) -> ValidationReport<DEFAULT_PUZZLE_SIZE> {
+    let mut report = ValidationReport::new();
+
+    for _ in 0..samples {
+        let puzzle_state = random_walk_puzzle_state(steps_back, rng);
+        let solution = solve_with_heuristic(puzzle_state, reference)
+            .expect("Generated puzzle state has to be solvable.");
+
+        report.check_state(puzzle_state, solution.steps().len() as u8, heuristic);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::{DisjointDatabases, ManhattanDistance};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    const SEED: u64 = 42;
+
+    /// Heuristic which overestimates distances twice.
+    struct DoubledManhattanDistance<const PUZZLE_SIZE: usize>(ManhattanDistance<PUZZLE_SIZE>);
+
+    impl<const PUZZLE_SIZE: usize> Heuristic<PUZZLE_SIZE> for DoubledManhattanDistance<PUZZLE_SIZE> {
+        fn calculate(&self, numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE]) -> u8 {
+            self.0.calculate(numbers) * 2
+        }
+    }
+
+    #[test]
+    fn exact_distances_work() {
+        let distances = exact_distances::<PUZZLE_SIZE>();
+
+        assert_eq!(12, distances.len());
+        assert_eq!(Some(&6), distances.values().max());
+
+        let distances = exact_distances::<BIGGER_PUZZLE_SIZE>();
+
+        assert_eq!(181_440, distances.len());
+        assert_eq!(Some(&31), distances.values().max());
+    }
+
+    #[test]
+    fn manhattan_distance_is_valid() {
+        let report = validate_exhaustive(&ManhattanDistance::<BIGGER_PUZZLE_SIZE>::new());
+
+        assert_eq!(181_440, report.checked_states());
+        assert!(report.inadmissible_states().is_empty());
+        assert!(report.inconsistent_moves().is_empty());
+
+        let average_ratio = report.average_ratio().unwrap();
+
+        assert!(average_ratio > 0.0 && average_ratio <= 1.0);
+    }
+
+    #[test]
+    fn invalid_heuristic_detected() {
+        let report = validate_exhaustive(&DoubledManhattanDistance(
+            ManhattanDistance::<PUZZLE_SIZE>::new(),
+        ));
+
+        assert_eq!(12, report.checked_states());
+        assert!(!report.inadmissible_states().is_empty());
+        assert!(!report.inconsistent_moves().is_empty());
+
+        for inadmissible_state in report.inadmissible_states() {
+            assert!(inadmissible_state.heuristic_value > inadmissible_state.distance);
+        }
+
+        assert!(report.average_ratio().unwrap() > 1.0);
+    }
+
+    #[test]
+    fn sampled_validation_works() {
+        let report = validate_sampled(
+            &DisjointDatabases::new(false),
+            &ManhattanDistance::new(),
+            5,
+            20,
+            &mut StdRng::seed_from_u64(SEED),
+        );
+
+        assert_eq!(5, report.checked_states());
+        assert!(report.inadmissible_states().is_empty());
+        assert!(report.inconsistent_moves().is_empty());
+    }
+}
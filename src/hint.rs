@@ -0,0 +1,290 @@
+/*!
+* Suggesting next move without solving whole game on every request.
+* Optimal solutions found within budget are cached for all states on their path, so following
+* hints is cheap. When budget is exceeded, move is chosen by bounded lookahead. Budget bounds only
+* the exact search, lookahead always searches [LOOKAHEAD_DEPTH] moves deep.
+*/
+
+use std::collections::HashMap;
+
+use crate::heuristics::Heuristic;
+use crate::{solve_with_options, Direction, PuzzleState, SearchError, SearchOptions};
+
+/// Depth of search used when optimal solution isn't found within budget.
+const LOOKAHEAD_DEPTH: u8 = 6;
+
+/// Suggested next move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    /// Direction of blank move.
+    pub direction: Direction,
+    /// Number of moves to solution (including suggested one).
+    pub remaining_moves: usize,
+    /**
+     * Whether move is on optimal path and `remaining_moves` is exact. Otherwise
+     * `remaining_moves` is an estimate found by lookahead.
+     */
+    pub exact: bool,
+}
+
+/// Cache of exact hints found by previous searches.
+#[derive(Debug, Clone)]
+pub struct HintCache<const PUZZLE_SIZE: usize> {
+    hints: HashMap<PuzzleState<PUZZLE_SIZE>, Hint>,
+}
+
+impl<const PUZZLE_SIZE: usize> HintCache<PUZZLE_SIZE> {
+    /// Creates empty [HintCache].
+    pub fn new() -> Self {
+        HintCache {
+            hints: HashMap::new(),
+        }
+    }
+
+    /// Returns number of cached hints.
+    pub fn len(&self) -> usize {
+        self.hints.len()
+    }
+
+    /// Checks if there are no cached hints.
+    pub fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+
+    /**
+     * Returns hint for `puzzle_state` (`None` if it is solved, unsolvable or blank can't move).
+     * If hint isn't cached, game is solved with `heuristic` visiting at most `budget` states.
+     */
+    pub fn hint(
+        &mut self,
+        puzzle_state: PuzzleState<PUZZLE_SIZE>,
+        heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+        budget: usize,
+    ) -> Option<Hint> {
+        if let Some(&hint) = self.hints.get(&puzzle_state) {
+            return Some(hint);
+        }
+
+        let options = SearchOptions::new().with_max_visited_states(budget);
+
+        match solve_with_options(puzzle_state, heuristic, options) {
+            Ok(solution) => {
+                let steps = solution.steps();
+                let mut curr_state = puzzle_state;
+
+                for (step_index, (&direction, &moved_tile)) in
+                    steps.iter().zip(solution.moved_tiles()).enumerate()
+                {
+                    self.hints.insert(
+                        curr_state,
+                        Hint {
+                            direction,
+                            remaining_moves: steps.len() - step_index,
+                            exact: true,
+                        },
+                    );
+
+                    curr_state = curr_state
+                        .create_tile_move_state(moved_tile, direction)
+                        .expect("Solution has to consist of possible moves.");
+                }
+
+                self.hints.get(&puzzle_state).copied()
+            }
+            Err(SearchError::NotSolvable | SearchError::UnsupportedHeuristic) => None,
+            Err(SearchError::LimitExceeded(_)) => lookahead_hint(puzzle_state, heuristic),
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Default for HintCache<PUZZLE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+* Returns hint for `puzzle_state` (`None` if it is solved, unsolvable or blank can't move) solving
+* game with `heuristic` visiting at most `budget` states. Use [HintCache] to reuse found solutions.
+*/
+pub fn hint<const PUZZLE_SIZE: usize>(
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    budget: usize,
+) -> Option<Hint> {
+    HintCache::new().hint(puzzle_state, heuristic, budget)
+}
+
+/**
+* Chooses move leading to state with smallest `g + h` value found by depth limited search.
+* State can't be solved (solution would be found within any budget). Returns `None` if blank can't
+* move.
+*/
+fn lookahead_hint<const PUZZLE_SIZE: usize>(
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+) -> Option<Hint> {
+    puzzle_state
+        .neighbours()
+        .map(|neighbour| {
+            let (direction, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+            let estimate =
+                lookahead_estimate(neighbour_state, direction, heuristic, LOOKAHEAD_DEPTH - 1);
+
+            Hint {
+                direction,
+                remaining_moves: estimate + 1,
+                exact: false,
+            }
+        })
+        .min_by_key(|hint| hint.remaining_moves)
+}
+
+/// Returns smallest `g + h` value of states at most `depth` moves from `puzzle_state`.
+fn lookahead_estimate<const PUZZLE_SIZE: usize>(
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    last_direction: Direction,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    depth: u8,
+) -> usize {
+    if puzzle_state.is_solved() {
+        return 0;
+    }
+
+    let heuristic_value = puzzle_state.calculate_heuristic(heuristic) as usize;
+
+    if depth == 0 {
+        return heuristic_value;
+    }
+
+    puzzle_state
+        .neighbours()
+        .map(|neighbour| neighbour.into_direction_and_puzzle_state())
+        .filter(|&(direction, _)| direction != last_direction.opposite())
+        .map(|(direction, neighbour_state)| {
+            lookahead_estimate(neighbour_state, direction, heuristic, depth - 1) + 1
+        })
+        .min()
+        .unwrap_or(heuristic_value)
+        // Admissible heuristic value is a lower bound, even if lookahead finds smaller values.
+        .max(heuristic_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::{DisjointDatabases, ManhattanDistance, PerfectHeuristic};
+    use crate::puzzle_state::coordinates::BoardCoordinates;
+    use crate::puzzle_state::obstacles::Obstacles;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    const BIG_BUDGET: usize = 1_000_000;
+
+    fn puzzle_state() -> PuzzleState<BIGGER_PUZZLE_SIZE> {
+        PuzzleState::new([
+            [Some(8), Some(6), Some(7)],
+            [Some(2), Some(5), Some(4)],
+            [Some(3), None, Some(1)],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn exact_hint_works() {
+        let manhattan_distance = ManhattanDistance::new();
+        let perfect_heuristic = PerfectHeuristic::new();
+
+        let hint = hint(puzzle_state(), &manhattan_distance, BIG_BUDGET).unwrap();
+        let next_state = puzzle_state().create_neighbour_move_state(hint.direction);
+
+        assert!(hint.exact);
+        assert_eq!(31, hint.remaining_moves);
+        assert_eq!(Some(30), perfect_heuristic.distance(&next_state));
+    }
+
+    #[test]
+    fn no_hint_for_solved_and_unsolvable() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        assert_eq!(
+            None,
+            hint(PuzzleState::solved(), &manhattan_distance, BIG_BUDGET)
+        );
+
+        let unsolvable_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), Some(5), Some(6)],
+            [Some(8), Some(7), None],
+        ])
+        .unwrap();
+
+        assert_eq!(
+            None,
+            hint(unsolvable_state, &manhattan_distance, BIG_BUDGET)
+        );
+    }
+
+    #[test]
+    fn hints_along_solution_are_cached() {
+        let manhattan_distance = ManhattanDistance::new();
+        let mut hint_cache = HintCache::new();
+        let mut curr_state = puzzle_state();
+
+        let first_hint = hint_cache
+            .hint(curr_state, &manhattan_distance, BIG_BUDGET)
+            .unwrap();
+
+        assert_eq!(31, hint_cache.len());
+
+        // Budget 0 would make any search fail, so hints have to come from cache.
+        for remaining_moves in (1..=first_hint.remaining_moves).rev() {
+            let hint = hint_cache.hint(curr_state, &manhattan_distance, 0).unwrap();
+
+            assert!(hint.exact);
+            assert_eq!(remaining_moves, hint.remaining_moves);
+
+            curr_state = curr_state.create_neighbour_move_state(hint.direction);
+        }
+
+        assert!(curr_state.is_solved());
+    }
+
+    #[test]
+    fn lookahead_hint_when_budget_exceeded() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        let lookahead_hint = hint(puzzle_state(), &manhattan_distance, 10).unwrap();
+        let heuristic_value = puzzle_state().calculate_heuristic(&manhattan_distance) as usize;
+
+        assert!(!lookahead_hint.exact);
+        assert!(lookahead_hint.remaining_moves <= 31);
+        assert!(lookahead_hint.remaining_moves >= heuristic_value);
+
+        let disjoint_databases = DisjointDatabases::new(false);
+        let hard_state =
+            PuzzleState::<4>::unrank(PuzzleState::<4>::solvable_states_count() / 2).unwrap();
+
+        let hard_state_hint = hint(hard_state, &disjoint_databases, 1000).unwrap();
+
+        assert!(!hard_state_hint.exact);
+        assert!(hard_state.can_move(hard_state_hint.direction));
+    }
+
+    #[test]
+    fn no_lookahead_hint_when_blank_cant_move() {
+        let obstacles =
+            Obstacles::new(&[BoardCoordinates::new(1, 2), BoardCoordinates::new(2, 1)]).unwrap();
+        let puzzle_state = "[2, 1, 3, 4, 5, 6, 7, 8, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+
+        assert_eq!(
+            None,
+            lookahead_hint(puzzle_state, &ManhattanDistance::new())
+        );
+        assert_eq!(None, hint(puzzle_state, &ManhattanDistance::new(), 0));
+    }
+}
@@ -5,20 +5,38 @@
 * TODO: Create better documentation.
 */
 
+pub mod analysis;
 pub mod astar_state;
+pub mod batch;
+pub mod closed_list;
+pub mod external_bfs;
+pub mod game;
 pub mod generator;
 pub mod heuristics;
+pub mod hint;
+pub mod observer;
+pub mod open_list;
+pub mod optimal_solutions;
+pub mod partial_goal;
 pub mod puzzle_state;
+pub mod search_tree;
+pub mod stats;
+pub mod terminal;
 
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
 
 use astar_state::AstarState;
+use closed_list::ClosedList;
 use heuristics::Heuristic;
+use observer::{NoObserver, SearchObserver};
+use open_list::{BucketOpenList, HeapOpenList, OpenList, OpenListKind, TieBreaking};
+use partial_goal::PartialGoal;
+use puzzle_state::locked_cells::LockedCells;
 
 pub use generator::generate_random_puzzle_state;
 pub use puzzle_state::direction::Direction;
 pub use puzzle_state::PuzzleState;
+pub use stats::SearchStats;
 
 /// Most common used puzzle size.
 pub const DEFAULT_PUZZLE_SIZE: usize = 4;
@@ -27,6 +45,8 @@ pub const DEFAULT_PUZZLE_SIZE: usize = 4;
 pub struct Solution {
     steps: Vec<Direction>,
     no_of_visited_states: usize,
+    stats: SearchStats,
+    moved_tiles: Vec<u8>,
 }
 
 impl Solution {
@@ -35,6 +55,21 @@ impl Solution {
         Solution {
             steps,
             no_of_visited_states,
+            stats: SearchStats::default(),
+            moved_tiles: vec![],
+        }
+    }
+
+    /// Sets statistics of search which found solution.
+    pub fn with_stats(self, stats: SearchStats) -> Self {
+        Solution { stats, ..self }
+    }
+
+    /// Sets numbers of tiles moved by every step.
+    pub fn with_moved_tiles(self, moved_tiles: Vec<u8>) -> Self {
+        Solution {
+            moved_tiles,
+            ..self
         }
     }
 
@@ -47,61 +82,329 @@ impl Solution {
     pub fn no_of_visited_states(&self) -> usize {
         self.no_of_visited_states
     }
+
+    /// Accessor for `stats` field.
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /**
+     * Accessor for `moved_tiles` field, numbers of tiles moved by every step. Only A* search
+     * records them (they are needed to replay solutions of states with several blanks).
+     */
+    pub fn moved_tiles(&self) -> &[u8] {
+        &self.moved_tiles
+    }
+}
+
+/// Limits and settings for A* search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    max_visited_states: Option<usize>,
+    tie_breaking: TieBreaking,
+    open_list: OpenListKind,
+}
+
+impl SearchOptions {
+    /// Creates new instance of [SearchOptions] without limits and with default tie breaking.
+    pub fn new() -> Self {
+        SearchOptions::default()
+    }
+
+    /// Limits number of visited states, search fails when solution isn't found within limit.
+    pub fn with_max_visited_states(self, max_visited_states: usize) -> Self {
+        SearchOptions {
+            max_visited_states: Some(max_visited_states),
+            ..self
+        }
+    }
+
+    /// Sets order of states with the same f value.
+    pub fn with_tie_breaking(self, tie_breaking: TieBreaking) -> Self {
+        SearchOptions {
+            tie_breaking,
+            ..self
+        }
+    }
+
+    /// Accessor for `max_visited_states` field.
+    pub fn max_visited_states(&self) -> Option<usize> {
+        self.max_visited_states
+    }
+
+    /// Sets implementation of open list.
+    pub fn with_open_list(self, open_list: OpenListKind) -> Self {
+        SearchOptions { open_list, ..self }
+    }
+
+    /// Accessor for `tie_breaking` field.
+    pub fn tie_breaking(&self) -> TieBreaking {
+        self.tie_breaking
+    }
+
+    /// Accessor for `open_list` field.
+    pub fn open_list(&self) -> OpenListKind {
+        self.open_list
+    }
+}
+
+/// Errors that can occur when searching for solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchError {
+    /// Initial state is not solvable (goal can't be reached from it).
+    NotSolvable,
+    /// Solution wasn't found within [SearchOptions] limits, statistics of stopped search.
+    LimitExceeded(SearchStats),
+    /// Heuristic doesn't support initial state (see [Heuristic::supports]).
+    UnsupportedHeuristic,
 }
 
 /**
 * Solves sliding puzzle game using given heuristic in A* algorithm.
 * Returns `Some(result)` if there exists solution or `None` if not.
 */
-pub fn solve_with_heuristic(
-    initial_state: PuzzleState<DEFAULT_PUZZLE_SIZE>,
-    heuristic: &dyn Heuristic<DEFAULT_PUZZLE_SIZE>,
+pub fn solve_with_heuristic<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
 ) -> Option<Solution> {
-    let mut curr_state = AstarState::inital(initial_state, heuristic).ok()?;
-    let mut last_directions = HashMap::new();
-    let mut frontier = BinaryHeap::new();
+    solve_with_options(initial_state, heuristic, SearchOptions::new()).ok()
+}
+
+/// Solves sliding puzzle game using given heuristic in A* algorithm within `options` limits.
+pub fn solve_with_options<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+) -> Result<Solution, SearchError> {
+    solve_with_observer(initial_state, heuristic, options, &mut NoObserver)
+}
+
+/**
+* Solves sliding puzzle game using given heuristic in A* algorithm within `options` limits,
+* notifying `observer` about search events.
+*/
+pub fn solve_with_observer<const PUZZLE_SIZE: usize, O: SearchObserver<PUZZLE_SIZE>>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+    observer: &mut O,
+) -> Result<Solution, SearchError> {
+    match options.open_list {
+        OpenListKind::Heap => solve_with_open_list(
+            initial_state,
+            heuristic,
+            options,
+            HeapOpenList::new(options.tie_breaking),
+            observer,
+        ),
+        OpenListKind::Buckets => solve_with_open_list(
+            initial_state,
+            heuristic,
+            options,
+            BucketOpenList::new(options.tie_breaking),
+            observer,
+        ),
+    }
+}
+
+/**
+* Solves sliding puzzle game using given heuristic in A* algorithm with given empty open list.
+* Open list kind and tie breaking in `options` are ignored.
+*/
+pub fn solve_with_open_list<const PUZZLE_SIZE: usize, L, O>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+    frontier: L,
+    observer: &mut O,
+) -> Result<Solution, SearchError>
+where
+    L: OpenList<PUZZLE_SIZE>,
+    O: SearchObserver<PUZZLE_SIZE>,
+{
+    if !heuristic.supports(&initial_state) {
+        return Err(SearchError::UnsupportedHeuristic);
+    }
+
+    let initial_state =
+        AstarState::inital(initial_state, heuristic).map_err(|_| SearchError::NotSolvable)?;
+
+    search(
+        initial_state,
+        heuristic,
+        options,
+        frontier,
+        observer,
+        |puzzle_state| puzzle_state.is_solved(),
+        LockedCells::new(),
+    )
+}
+
+/**
+* Finds shortest sequence of moves from `initial_state` to state where `partial_goal` is reached,
+* never moving tiles from `locked_cells`. `heuristic` has to be admissible for `partial_goal`
+* (e.g. [PartialManhattanDistance](heuristics::PartialManhattanDistance)).
+* Returns [SearchError::NotSolvable] if goal can't be reached, which is known only after all
+* reachable states are visited (the whole board for 15 puzzle), so use
+* [solve_partial_with_options] to limit the search.
+*/
+pub fn solve_partial<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    partial_goal: &PartialGoal<PUZZLE_SIZE>,
+    locked_cells: LockedCells<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+) -> Result<Solution, SearchError> {
+    solve_partial_with_options(
+        initial_state,
+        partial_goal,
+        locked_cells,
+        heuristic,
+        SearchOptions::new(),
+    )
+}
+
+/**
+* Finds shortest sequence of moves reaching `partial_goal` like [solve_partial] within `options`
+* limits.
+*/
+pub fn solve_partial_with_options<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    partial_goal: &PartialGoal<PUZZLE_SIZE>,
+    locked_cells: LockedCells<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+) -> Result<Solution, SearchError> {
+    if !heuristic.supports(&initial_state) {
+        return Err(SearchError::UnsupportedHeuristic);
+    }
+
+    let initial_state = AstarState::initial_unchecked(initial_state, heuristic);
+    let is_goal = |puzzle_state: &PuzzleState<PUZZLE_SIZE>| partial_goal.is_reached(puzzle_state);
+
+    match options.open_list {
+        OpenListKind::Heap => search(
+            initial_state,
+            heuristic,
+            options,
+            HeapOpenList::new(options.tie_breaking),
+            &mut NoObserver,
+            is_goal,
+            locked_cells,
+        ),
+        OpenListKind::Buckets => search(
+            initial_state,
+            heuristic,
+            options,
+            BucketOpenList::new(options.tie_breaking),
+            &mut NoObserver,
+            is_goal,
+            locked_cells,
+        ),
+    }
+}
+
+/// A* search from `curr_state` to state satisfying `is_goal`.
+fn search<const PUZZLE_SIZE: usize, L, O>(
+    mut curr_state: AstarState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+    mut frontier: L,
+    observer: &mut O,
+    is_goal: impl Fn(&PuzzleState<PUZZLE_SIZE>) -> bool,
+    locked_cells: LockedCells<PUZZLE_SIZE>,
+) -> Result<Solution, SearchError>
+where
+    L: OpenList<PUZZLE_SIZE>,
+    O: SearchObserver<PUZZLE_SIZE>,
+{
+    let start_time = Instant::now();
+    let mut last_directions = ClosedList::new();
+    let mut stats = SearchStats::new(curr_state.f_value());
+    let mut f_bound = None;
 
     // So we can pop something in first iteration.
-    frontier.push(Reverse(curr_state.clone()));
+    frontier.push(curr_state.clone());
 
-    while !curr_state.is_solved() {
-        // There have to be elements in frontier if not solved yet.
-        curr_state = frontier.pop().unwrap().0;
+    while !is_goal(&curr_state.puzzle_state()) {
+        // Frontier is empty only if goal can't be reached.
+        curr_state = frontier.pop().ok_or(SearchError::NotSolvable)?;
 
-        let state_not_visited = last_directions.get(&curr_state.puzzle_state()).is_none();
+        let state_not_visited = !last_directions.contains(&curr_state.puzzle_state());
 
         if state_not_visited {
-            last_directions.insert(curr_state.puzzle_state(), curr_state.last_direction());
+            if Some(last_directions.len()) == options.max_visited_states {
+                stats.stop(start_time.elapsed());
 
-            let neighbours = curr_state.neighbours();
+                return Err(SearchError::LimitExceeded(stats));
+            }
+
+            last_directions.insert_with_moved_blank(
+                curr_state.puzzle_state(),
+                curr_state.last_direction(),
+                curr_state.moved_blank(),
+            );
+            stats.record_expansion(curr_state.f_value());
+
+            if f_bound < Some(curr_state.f_value()) {
+                f_bound = Some(curr_state.f_value());
+                observer.on_new_f_bound(curr_state.f_value());
+            }
+
+            observer.on_expand(&curr_state);
+
+            let neighbours = curr_state.neighbours().with_locked_cells(locked_cells);
 
             for neighbour in neighbours {
-                let (direction, puzzle_state) = neighbour.into_direction_and_puzzle_state();
+                let (_, puzzle_state) = neighbour.into_direction_and_puzzle_state();
 
-                if last_directions.get(&puzzle_state).is_none() {
+                if !last_directions.contains(&puzzle_state) {
                     let moved_to_neighbour_state =
-                        curr_state.moved_to_neighbour(direction, puzzle_state, heuristic);
+                        curr_state.moved_to_neighbour(neighbour, heuristic);
 
-                    frontier.push(Reverse(moved_to_neighbour_state));
+                    observer.on_generate(&moved_to_neighbour_state);
+                    frontier.push(moved_to_neighbour_state);
+                    stats.record_generation();
+                } else {
+                    observer.on_duplicate(puzzle_state);
+                    stats.record_duplicate();
                 }
             }
+
+            stats.record_frontier_size(frontier.len());
+        } else {
+            observer.on_duplicate(curr_state.puzzle_state());
+            stats.record_duplicate();
         }
     }
 
-    let solution = Solution::new(
-        curr_state.create_route(&last_directions),
-        last_directions.len(),
-    );
+    let (steps, moved_tiles): (Vec<_>, Vec<_>) = curr_state
+        .create_route_with_moved_tiles(&last_directions)
+        .into_iter()
+        .unzip();
+    observer.on_solution(&steps, &moved_tiles);
+    stats.finish(steps.len(), start_time.elapsed());
+
+    let solution = Solution::new(steps, last_directions.len())
+        .with_stats(stats)
+        .with_moved_tiles(moved_tiles);
 
-    Some(solution)
+    Ok(solution)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::collections::{HashMap, VecDeque};
+
     use heuristics::disjoint_databases::DisjointDatabases;
     use heuristics::manhattan_distance::ManhattanDistance;
+    use heuristics::{ObstacleDistance, PartialManhattanDistance};
+    use puzzle_state::coordinates::BoardCoordinates;
+    use puzzle_state::obstacles::Obstacles;
+    use puzzle_state::topology::Topology;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
 
     #[test]
     fn solve_on_solved_works() {
@@ -138,6 +441,163 @@ mod tests {
         assert!(solution.is_none());
     }
 
+    #[test]
+    fn solve_with_limit() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        let puzzle_state = PuzzleState::<DEFAULT_PUZZLE_SIZE>::new([
+            [None, Some(2), Some(3), Some(4)],
+            [Some(1), Some(6), Some(7), Some(8)],
+            [Some(5), Some(10), Some(11), Some(12)],
+            [Some(9), Some(13), Some(14), Some(15)],
+        ])
+        .unwrap();
+
+        let solution = solve_with_heuristic(puzzle_state, &manhattan_distance).unwrap();
+        let visited_states = solution.no_of_visited_states();
+
+        let limited_solution = solve_with_options(
+            puzzle_state,
+            &manhattan_distance,
+            SearchOptions::new().with_max_visited_states(visited_states),
+        )
+        .unwrap();
+
+        assert_eq!(solution.steps(), limited_solution.steps());
+
+        match solve_with_options(
+            puzzle_state,
+            &manhattan_distance,
+            SearchOptions::new().with_max_visited_states(visited_states - 1),
+        ) {
+            Err(SearchError::LimitExceeded(stats)) => {
+                assert_eq!(visited_states - 1, stats.expanded_states());
+                assert_eq!(0, stats.solution_length());
+            }
+            result => panic!("Limit has to be exceeded, got: {:?}", result.err()),
+        }
+
+        let unsolvable_state = PuzzleState::<DEFAULT_PUZZLE_SIZE>::new([
+            [Some(2), Some(1), Some(3), Some(4)],
+            [Some(5), Some(6), Some(7), Some(8)],
+            [Some(9), Some(10), Some(11), Some(12)],
+            [Some(13), Some(14), Some(15), None],
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Some(SearchError::NotSolvable),
+            solve_with_options(unsolvable_state, &manhattan_distance, SearchOptions::new()).err()
+        );
+    }
+
+    #[test]
+    fn solution_stats_work() {
+        let manhattan_distance = ManhattanDistance::new();
+
+        let puzzle_state = PuzzleState::<DEFAULT_PUZZLE_SIZE>::new([
+            [None, Some(2), Some(3), Some(4)],
+            [Some(1), Some(6), Some(7), Some(8)],
+            [Some(5), Some(10), Some(11), Some(12)],
+            [Some(9), Some(13), Some(14), Some(15)],
+        ])
+        .unwrap();
+
+        let solution = solve_with_heuristic(puzzle_state, &manhattan_distance).unwrap();
+        let stats = solution.stats();
+
+        assert_eq!(solution.no_of_visited_states(), stats.expanded_states());
+        assert_eq!(6, stats.solution_length());
+        assert_eq!(6, stats.initial_heuristic_value());
+        assert_eq!(0, stats.reopened_states());
+        assert_eq!(stats.generated_states() + 1, stats.heuristic_evaluations());
+        assert_eq!(
+            stats.expanded_states(),
+            stats.expanded_states_per_f_value().values().sum::<usize>()
+        );
+        assert!(stats.peak_frontier_size() > 0);
+        assert!(stats.effective_branching_factor().unwrap() >= 1.0);
+    }
+
+    #[test]
+    fn tie_breaking_is_deterministic() {
+        let manhattan_distance = ManhattanDistance::new();
+        let puzzle_state = "[3, 11, 7, 4, 1, 6, 2, 10, 5, 15, , 14, 13, 8, 9, 12]"
+            .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+            .unwrap();
+
+        for tie_breaking in [TieBreaking::HigherG, TieBreaking::Lifo, TieBreaking::Fifo] {
+            let options = SearchOptions::new().with_tie_breaking(tie_breaking);
+
+            let solution = solve_with_options(puzzle_state, &manhattan_distance, options).unwrap();
+            let repeated_solution =
+                solve_with_options(puzzle_state, &manhattan_distance, options).unwrap();
+
+            assert_eq!(solution.steps(), repeated_solution.steps());
+            assert_eq!(
+                solution.stats().expanded_states_per_f_value(),
+                repeated_solution.stats().expanded_states_per_f_value()
+            );
+            assert_eq!(
+                solution.stats().generated_states(),
+                repeated_solution.stats().generated_states()
+            );
+        }
+    }
+
+    #[test]
+    fn higher_g_tie_breaking_reduces_last_layer_expansions() {
+        let manhattan_distance = ManhattanDistance::new();
+        let puzzle_state = "[3, 11, 7, 4, 1, 6, 2, 10, 5, 15, , 14, 13, 8, 9, 12]"
+            .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+            .unwrap();
+
+        let last_layer_expansions = |tie_breaking| {
+            let options = SearchOptions::new().with_tie_breaking(tie_breaking);
+            let solution = solve_with_options(puzzle_state, &manhattan_distance, options).unwrap();
+            let (_, &expanded_states) = solution
+                .stats()
+                .expanded_states_per_f_value()
+                .last_key_value()
+                .unwrap();
+
+            (solution.steps().len(), expanded_states)
+        };
+
+        let (higher_g_length, higher_g_expansions) = last_layer_expansions(TieBreaking::HigherG);
+        let (fifo_length, fifo_expansions) = last_layer_expansions(TieBreaking::Fifo);
+
+        assert_eq!(higher_g_length, fifo_length);
+        assert!(higher_g_expansions * 10 < fifo_expansions);
+    }
+
+    #[test]
+    fn bucket_open_list_gives_same_results() {
+        let manhattan_distance = ManhattanDistance::new();
+        let puzzle_state = "[3, 11, 7, 4, 1, 6, 2, 10, 5, 15, , 14, 13, 8, 9, 12]"
+            .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+            .unwrap();
+
+        for tie_breaking in [TieBreaking::HigherG, TieBreaking::Lifo, TieBreaking::Fifo] {
+            let options = SearchOptions::new().with_tie_breaking(tie_breaking);
+
+            let heap_solution =
+                solve_with_options(puzzle_state, &manhattan_distance, options).unwrap();
+            let bucket_solution = solve_with_options(
+                puzzle_state,
+                &manhattan_distance,
+                options.with_open_list(OpenListKind::Buckets),
+            )
+            .unwrap();
+
+            assert_eq!(heap_solution.steps(), bucket_solution.steps());
+            assert_eq!(
+                heap_solution.stats().expanded_states_per_f_value(),
+                bucket_solution.stats().expanded_states_per_f_value()
+            );
+        }
+    }
+
     #[test]
     fn solving_with_manhattan_distance_works() {
         let manhattan_distance = ManhattanDistance::new();
@@ -219,4 +679,367 @@ mod tests {
             solution.unwrap().steps()
         );
     }
+
+    #[test]
+    fn reflected_disjoint_databases_visit_fewer_states() {
+        let disjoint_databases = DisjointDatabases::new(false);
+        let reflected_disjoint_databases = DisjointDatabases::new(false).with_reflection(true);
+
+        let puzzle_states = [
+            "[3, 11, 7, 4, 1, 6, 2, 10, 5, 15, , 14, 13, 8, 9, 12]",
+            "[2, 5, 1, 3, 6, 11, 4, 7, , 12, 10, 8, 9, 13, 14, 15]",
+            "[2, 3, 4, 8, 10, 11, 7, 15, , 9, 5, 13, 1, 6, 14, 12]",
+        ];
+
+        let mut no_of_visited_states = 0;
+        let mut reflected_no_of_visited_states = 0;
+
+        for puzzle_state in puzzle_states {
+            let puzzle_state = puzzle_state
+                .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+                .unwrap();
+
+            let solution = solve_with_heuristic(puzzle_state, &disjoint_databases).unwrap();
+            let reflected_solution =
+                solve_with_heuristic(puzzle_state, &reflected_disjoint_databases).unwrap();
+
+            assert_eq!(solution.steps().len(), reflected_solution.steps().len());
+
+            no_of_visited_states += solution.no_of_visited_states();
+            reflected_no_of_visited_states += reflected_solution.no_of_visited_states();
+        }
+
+        assert!(reflected_no_of_visited_states < no_of_visited_states);
+    }
+
+    /// Returns states visited when performing `steps` from `puzzle_state`.
+    fn visited_states<const SIZE: usize>(
+        puzzle_state: PuzzleState<SIZE>,
+        steps: &[Direction],
+    ) -> Vec<PuzzleState<SIZE>> {
+        steps
+            .iter()
+            .scan(puzzle_state, |puzzle_state, &direction| {
+                *puzzle_state = puzzle_state.create_neighbour_move_state(direction);
+
+                Some(*puzzle_state)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn solve_partial_reaches_partial_goal() {
+        let puzzle_state = "[3, 11, 7, 4, 1, 6, 2, 10, 5, 15, , 14, 13, 8, 9, 12]"
+            .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+            .unwrap();
+        let partial_goal = PartialGoal::from_tiles(&[1, 2, 3, 4]).unwrap();
+
+        let partial_solution = solve_partial(
+            puzzle_state,
+            &partial_goal,
+            LockedCells::new(),
+            &PartialManhattanDistance::new(&partial_goal),
+        )
+        .unwrap();
+        let solution = solve_with_heuristic(puzzle_state, &ManhattanDistance::new()).unwrap();
+        let reached_states = visited_states(puzzle_state, partial_solution.steps());
+
+        assert!(partial_goal.is_reached(reached_states.last().unwrap()));
+        assert!(reached_states[..reached_states.len() - 1]
+            .iter()
+            .all(|puzzle_state| !partial_goal.is_reached(puzzle_state)));
+        assert!(partial_solution.steps().len() < solution.steps().len());
+    }
+
+    #[test]
+    fn solve_partial_with_full_goal_is_optimal() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(8), Some(6), Some(7)],
+            [Some(2), Some(5), Some(4)],
+            [Some(3), None, Some(1)],
+        ])
+        .unwrap();
+        let partial_goal = PartialGoal::from_tiles(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+        let partial_solution = solve_partial(
+            puzzle_state,
+            &partial_goal,
+            LockedCells::new(),
+            &PartialManhattanDistance::new(&partial_goal),
+        )
+        .unwrap();
+
+        assert_eq!(31, partial_solution.steps().len());
+    }
+
+    #[test]
+    fn solve_partial_never_moves_locked_tiles() {
+        // Scrambled without moving the first row.
+        let scrambling_steps = [
+            Direction::Up,
+            Direction::Left,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+        ];
+        let puzzle_state = *visited_states(
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved(),
+            &scrambling_steps,
+        )
+        .last()
+        .unwrap();
+        let partial_goal = PartialGoal::from_tiles(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let locked_cells = LockedCells::of_tiles(&puzzle_state, &[1, 2, 3]);
+
+        let locked_solution = solve_partial(
+            puzzle_state,
+            &partial_goal,
+            locked_cells,
+            &PartialManhattanDistance::new(&partial_goal),
+        )
+        .unwrap();
+        let solution = solve_with_heuristic(puzzle_state, &ManhattanDistance::new()).unwrap();
+        let reached_states = visited_states(puzzle_state, locked_solution.steps());
+
+        assert!(reached_states.last().unwrap().is_solved());
+        assert!(reached_states.iter().all(|reached_state| {
+            (0..BIGGER_PUZZLE_SIZE as u8).all(|column| {
+                reached_state.number_at(BoardCoordinates::new(0, column)) == Some(column + 1)
+            })
+        }));
+        assert!(solution.steps().len() <= locked_solution.steps().len());
+    }
+
+    #[test]
+    fn solve_partial_reports_unreachable_goal() {
+        // Tile 1 is locked outside of its target cell.
+        let puzzle_state = "[2, 1, 3, 4, 5, 6, 7, 8, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let partial_goal = PartialGoal::from_tiles(&[1]).unwrap();
+
+        let result = solve_partial(
+            puzzle_state,
+            &partial_goal,
+            LockedCells::of_tiles(&puzzle_state, &[1]),
+            &PartialManhattanDistance::new(&partial_goal),
+        );
+
+        assert!(matches!(result, Err(SearchError::NotSolvable)));
+
+        let limited_result = solve_partial_with_options(
+            puzzle_state,
+            &partial_goal,
+            LockedCells::of_tiles(&puzzle_state, &[1]),
+            &PartialManhattanDistance::new(&partial_goal),
+            SearchOptions::new().with_max_visited_states(100),
+        );
+
+        assert!(matches!(limited_result, Err(SearchError::LimitExceeded(_))));
+
+        // Partial goal can be reached even from unsolvable state.
+        let solution = solve_partial(
+            puzzle_state,
+            &partial_goal,
+            LockedCells::new(),
+            &PartialManhattanDistance::new(&partial_goal),
+        )
+        .unwrap();
+
+        assert!(!puzzle_state.is_solvable());
+        assert!(partial_goal.is_reached(
+            visited_states(puzzle_state, solution.steps())
+                .last()
+                .unwrap()
+        ));
+    }
+
+    #[test]
+    fn solve_goes_around_obstacles() {
+        let obstacles =
+            Obstacles::<BIGGER_PUZZLE_SIZE>::new(&[BoardCoordinates::new(1, 1)]).unwrap();
+        let obstacle_distance = ObstacleDistance::new(&obstacles);
+
+        // Blank has to go around blocked centre to the opposite corner.
+        let puzzle_state = "[, 1, 2, 4, 5, 3, 7, 8, 6]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+        let solution = solve_with_options(
+            puzzle_state,
+            &obstacle_distance,
+            SearchOptions::new().with_max_visited_states(1000),
+        )
+        .unwrap();
+        let reached_states = visited_states(puzzle_state, solution.steps());
+
+        assert_eq!(4, solution.steps().len());
+        assert!(reached_states.last().unwrap().is_solved());
+        assert!(reached_states
+            .iter()
+            .all(|reached_state| reached_state.number_at(BoardCoordinates::new(1, 1)) == Some(5)));
+
+        // Solvable without obstacles, but tiles on ring can't change their order.
+        let puzzle_state = "[2, 1, 3, 4, 5, 6, 8, 7, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+
+        assert!(solve_with_heuristic(puzzle_state, &ManhattanDistance::new()).is_some());
+        assert!(matches!(
+            solve_with_options(
+                puzzle_state.with_obstacles(obstacles),
+                &obstacle_distance,
+                SearchOptions::new()
+            ),
+            Err(SearchError::NotSolvable)
+        ));
+    }
+
+    /// Replays `solution` by moving its tiles, which works also with several blanks.
+    fn replayed_state<const SIZE: usize>(
+        puzzle_state: PuzzleState<SIZE>,
+        solution: &Solution,
+    ) -> PuzzleState<SIZE> {
+        solution.steps().iter().zip(solution.moved_tiles()).fold(
+            puzzle_state,
+            |puzzle_state, (&direction, &moved_tile)| {
+                puzzle_state
+                    .create_tile_move_state(moved_tile, direction)
+                    .unwrap()
+            },
+        )
+    }
+
+    #[test]
+    fn solve_with_several_blanks_is_optimal() {
+        let puzzle_state = "[7, , 2, 3, 6, 5, , 1, 4]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let solution = solve_with_heuristic(puzzle_state, &ManhattanDistance::new()).unwrap();
+
+        // Breadth first search from solved state for optimal solution length.
+        let solved_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::solved_with_blanks(2);
+        let mut distances = HashMap::from([(solved_state, 0)]);
+        let mut queue = VecDeque::from([solved_state]);
+
+        while let Some(visited_state) = queue.pop_front() {
+            let distance = distances[&visited_state];
+
+            for neighbour in visited_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                distances.entry(neighbour_state).or_insert_with(|| {
+                    queue.push_back(neighbour_state);
+                    distance + 1
+                });
+            }
+        }
+
+        assert!(puzzle_state.is_solvable());
+        assert_eq!(distances[&puzzle_state], solution.steps().len());
+        assert_eq!(solution.steps().len(), solution.moved_tiles().len());
+        assert!(replayed_state(puzzle_state, &solution).is_solved());
+    }
+
+    #[test]
+    fn several_blanks_are_rejected_by_disjoint_databases() {
+        let puzzle_state = "[1, 2, 3, 4, 5, 6, , 8, 9, 10, 7, 12, 13, 11, , 14]"
+            .parse::<PuzzleState<DEFAULT_PUZZLE_SIZE>>()
+            .unwrap();
+        let disjoint_databases = DisjointDatabases::new(false);
+        let combined_heuristic = heuristics::MaxHeuristic::new(vec![
+            Box::new(ManhattanDistance::new()),
+            Box::new(DisjointDatabases::new(false)),
+        ]);
+
+        assert_eq!(
+            Some(SearchError::UnsupportedHeuristic),
+            solve_with_options(puzzle_state, &disjoint_databases, SearchOptions::new()).err()
+        );
+        assert_eq!(
+            Some(SearchError::UnsupportedHeuristic),
+            solve_with_options(puzzle_state, &combined_heuristic, SearchOptions::new()).err()
+        );
+        assert!(solve_with_heuristic(puzzle_state, &ManhattanDistance::new()).is_some());
+    }
+
+    #[test]
+    fn solve_on_toroidal_board_is_optimal() {
+        // Unsolvable on flat board, on toroidal board of odd size every state is solvable.
+        let puzzle_state = "[2, 1, 3, 4, 5, 6, 7, 8, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_topology(Topology::Toroidal);
+        let manhattan_distance = ManhattanDistance::new().with_topology(Topology::Toroidal);
+        let solution = solve_with_heuristic(puzzle_state, &manhattan_distance).unwrap();
+
+        let solved_state =
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved().with_topology(Topology::Toroidal);
+        let mut distances = HashMap::from([(solved_state, 0)]);
+        let mut queue = VecDeque::from([solved_state]);
+
+        while let Some(visited_state) = queue.pop_front() {
+            let distance = distances[&visited_state];
+
+            for neighbour in visited_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                distances.entry(neighbour_state).or_insert_with(|| {
+                    queue.push_back(neighbour_state);
+                    distance + 1
+                });
+            }
+        }
+
+        assert_eq!(
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solvable_states_count() as usize * 2,
+            distances.len()
+        );
+        assert_eq!(distances[&puzzle_state], solution.steps().len());
+        assert!(visited_states(puzzle_state, solution.steps())
+            .last()
+            .unwrap()
+            .is_solved());
+    }
+
+    #[test]
+    fn heuristics_for_other_board_are_rejected() {
+        let puzzle_state = "[2, 1, 3, 4, 5, 6, 7, 8, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_topology(Topology::Toroidal);
+        let obstacles = Obstacles::new(&[BoardCoordinates::new(1, 1)]).unwrap();
+
+        assert_eq!(
+            Some(SearchError::UnsupportedHeuristic),
+            solve_with_options(
+                puzzle_state,
+                &ManhattanDistance::new(),
+                SearchOptions::new()
+            )
+            .err()
+        );
+        assert_eq!(
+            Some(SearchError::UnsupportedHeuristic),
+            solve_with_options(
+                puzzle_state,
+                &ObstacleDistance::new(&obstacles),
+                SearchOptions::new()
+            )
+            .err()
+        );
+        assert!(solve_with_options(
+            puzzle_state,
+            &ObstacleDistance::new(&Obstacles::default()).with_topology(Topology::Toroidal),
+            SearchOptions::new()
+        )
+        .is_ok());
+    }
 }
@@ -1,59 +1,873 @@
-use clap::{Parser, ValueEnum};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use puzzle::heuristics::{DisjointDatabases, Heuristic, ManhattanDistance};
-use puzzle::PuzzleState;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
-/// Available heuristics
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum AvailableHeuristics {
-    /// Manhattan Distance heuristic
-    ManhattanDistance,
-    /// Disjoint Databases heuristic
-    DisjointDatabases,
-}
+use puzzle::analysis;
+use puzzle::external_bfs::ExternalBfs;
+use puzzle::game::Game;
+use puzzle::heuristics::spec::HeuristicName;
+use puzzle::heuristics::validate::{self, ValidationReport};
+use puzzle::heuristics::{
+    DisjointDatabases, Heuristic, HeuristicSpec, ManhattanDistance, PerfectHeuristic,
+};
+use puzzle::observer::{JsonLinesObserver, ProgressObserver};
+use puzzle::open_list::{OpenListKind, TieBreaking};
+use puzzle::optimal_solutions;
+use puzzle::puzzle_state::topology::Topology;
+use puzzle::search_tree::SearchTreeRecorder;
+use puzzle::terminal::{self, Playback};
+use puzzle::{batch, generator};
+use puzzle::{PuzzleState, SearchOptions, SearchStats};
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// Heuristic to use.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    solve_args: SolveArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check if heuristic is admissible and consistent.
+    CheckHeuristic(CheckHeuristicArgs),
+    /// Generate puzzle states with given optimal solution length in batch file format.
+    Generate(GenerateArgs),
+    /// Play puzzle in terminal.
+    Play(PlayArgs),
+    /// Analyze state space of puzzle.
+    #[command(subcommand)]
+    Analyze(AnalyzeCommand),
+}
+
+#[derive(Subcommand)]
+enum AnalyzeCommand {
+    /// Count states at each distance from solution using breadth-first search.
+    Layers(LayersArgs),
+}
+
+/// Solves given or random puzzle state (used when no command is given).
+#[derive(Args)]
+struct SolveArgs {
+    /**
+     * Heuristic to use: manhattan-distance (manhattan), disjoint-databases (pdb), perfect (for
+     * puzzle size up to 3) or their combination using max(...) and sum(...),
+     * e.g. max(manhattan,pdb).
+     */
+    #[arg(long, required = true)]
+    heuristic: Option<HeuristicSpec>,
+
+    /// Also look up disjoint databases for board reflected about main diagonal.
+    #[arg(long)]
+    reflection: bool,
+
+    /// Show solution moves one by one in terminal.
     #[arg(long)]
-    heuristic: AvailableHeuristics,
+    animate: bool,
+
+    /// Show next solution move after key press instead of delay (implies --animate).
+    #[arg(long)]
+    step: bool,
+
+    /// Delay between solution moves in milliseconds when animating.
+    #[arg(long, default_value_t = 500)]
+    delay: u64,
+
+    /// Order of states with the same f value in open list.
+    #[arg(long, value_enum, default_value_t = TieBreakingArg::HigherG)]
+    tie_breaking: TieBreakingArg,
+
+    /// Open list implementation.
+    #[arg(long, value_enum, default_value_t = OpenListArg::Heap)]
+    open_list: OpenListArg,
+
+    /// Print search statistics.
+    #[arg(long)]
+    stats: bool,
+
+    /// Write search statistics as JSON to given file.
+    #[arg(long, value_name = "FILE")]
+    stats_json: Option<PathBuf>,
+
+    /// Print search progress every N expanded states.
+    #[arg(long, value_name = "N")]
+    progress: Option<usize>,
+
+    /// Write search events as JSON Lines to given file.
+    #[arg(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    /// Write explored part of search as Graphviz DOT graph to given file.
+    #[arg(long, value_name = "FILE")]
+    dot: Option<PathBuf>,
+
+    /// Maximum number of expanded states written to DOT file.
+    #[arg(long, default_value_t = 1000)]
+    dot_max_nodes: usize,
+
+    /// Count all optimal solutions and print at most N of them.
+    #[arg(long, value_name = "N")]
+    all_optimal: Option<usize>,
+
+    #[command(flatten)]
+    initial_state_args: InitialStateArgs,
+}
+
+#[derive(Args)]
+struct InitialStateArgs {
+    /// Generator of random initial puzzle state (used when no puzzle state is given).
+    #[arg(long, value_enum, default_value_t = Generator::Walk)]
+    generator: Generator,
+
+    /// Seed for random initial puzzle state (random seed is used and printed when not given).
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Board topology.
+    #[arg(long, value_enum, default_value_t = TopologyArg::Flat)]
+    topology: TopologyArg,
 
     /// Initial puzzle state
     puzzle_state: Option<String>,
 }
 
+/// Generators of random puzzle states.
+#[derive(Clone, Copy, ValueEnum)]
+enum Generator {
+    /// Uniformly random solvable state.
+    Uniform,
+    /// Random walk from solved state without undoing moves.
+    Walk,
+}
+
+/// Board topologies.
+#[derive(Clone, Copy, ValueEnum)]
+enum TopologyArg {
+    /// Board edges block moves.
+    Flat,
+    /// Moving off one edge wraps to the opposite edge.
+    Toroidal,
+}
+
+impl From<TopologyArg> for Topology {
+    fn from(topology: TopologyArg) -> Self {
+        match topology {
+            TopologyArg::Flat => Topology::Flat,
+            TopologyArg::Toroidal => Topology::Toroidal,
+        }
+    }
+}
+
+/// Tie breaking policies of A* open list.
+#[derive(Clone, Copy, ValueEnum)]
+enum TieBreakingArg {
+    /// Prefer deeper states (higher g, lower h), then last inserted.
+    HigherG,
+    /// Prefer last inserted states.
+    Lifo,
+    /// Prefer first inserted states.
+    Fifo,
+}
+
+impl From<TieBreakingArg> for TieBreaking {
+    fn from(tie_breaking: TieBreakingArg) -> Self {
+        match tie_breaking {
+            TieBreakingArg::HigherG => TieBreaking::HigherG,
+            TieBreakingArg::Lifo => TieBreaking::Lifo,
+            TieBreakingArg::Fifo => TieBreaking::Fifo,
+        }
+    }
+}
+
+/// Open list implementations.
+#[derive(Clone, Copy, ValueEnum)]
+enum OpenListArg {
+    /// Binary heap.
+    Heap,
+    /// Array of buckets indexed by f value.
+    Buckets,
+}
+
+impl From<OpenListArg> for OpenListKind {
+    fn from(open_list: OpenListArg) -> Self {
+        match open_list {
+            OpenListArg::Heap => OpenListKind::Heap,
+            OpenListArg::Buckets => OpenListKind::Buckets,
+        }
+    }
+}
+
+#[derive(Args)]
+struct CheckHeuristicArgs {
+    /// Heuristic to check (same syntax as for solving).
+    #[arg(long)]
+    heuristic: HeuristicSpec,
+
+    /// Also look up disjoint databases for board reflected about main diagonal.
+    #[arg(long)]
+    reflection: bool,
+
+    /**
+     * Puzzle size of square board. Sizes 2 and 3 are checked on all states, size 4 on sampled
+     * ones. Rectangular boards (such as 2x3) aren't supported.
+     */
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(2..=4))]
+    size: u8,
+
+    /// Number of sampled states (size 4 only).
+    #[arg(long, default_value_t = 100)]
+    samples: usize,
+
+    /// Number of random moves back from solution for sampled states (size 4 only).
+    #[arg(long, default_value_t = 30)]
+    steps_back: usize,
+
+    /// Admissible heuristic used to find exact distances for sampled states (size 4 only).
+    #[arg(long, default_value = "manhattan")]
+    reference: HeuristicSpec,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /**
+     * Number of moves in optimal solution of generated states. For size 4 distances over 16 are
+     * found by A* with consistent disjoint databases heuristic.
+     */
+    #[arg(long)]
+    distance: u8,
+
+    /// Number of generated states (all states are generated if there are fewer of them).
+    #[arg(long, default_value_t = 1)]
+    count: usize,
+
+    /// Puzzle size. States for sizes 2 and 3 are chosen from all states with given distance.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(2..=4))]
+    size: u8,
+
+    /// Seed for random choice of states (random seed is used and printed when not given).
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /**
+     * Heuristic used for hints (same syntax as for solving). Defaults to disjoint-databases for
+     * size 4 and perfect for smaller sizes.
+     */
+    #[arg(long)]
+    heuristic: Option<HeuristicSpec>,
+
+    /// Puzzle size.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(2..=4))]
+    size: u8,
+
+    #[command(flatten)]
+    initial_state_args: InitialStateArgs,
+}
+
+#[derive(Args)]
+struct LayersArgs {
+    /// Puzzle size.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(2..=4))]
+    size: u8,
+
+    /// Stop search after layer with given distance (required for size 4 in memory).
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Number of printed example states from the last layer.
+    #[arg(long, default_value_t = 3)]
+    examples: usize,
+
+    /// Keep layers in files in given directory instead of memory (search can be resumed).
+    #[arg(long)]
+    external: Option<PathBuf>,
+}
+
 const PUZZLE_SIZE: usize = 4;
 const MAX_STEPS_BACK: usize = 100;
+const MAX_REPORTED_ERRORS: usize = 10;
+const MAX_PERFECT_PUZZLE_SIZE: usize = 3;
+const MAX_LAYER_DISTANCE: u8 = 16;
+const MAX_SAMPLING_ATTEMPTS: usize = 1000;
+/// Distances of the hardest states for puzzle sizes 2, 3 and 4.
+const MAX_DISTANCES: [u8; 3] = [6, 31, 80];
+const FLAT_TOPOLOGY_ONLY_ERROR: &str =
+    "Only manhattan distance heuristic supports toroidal topology.";
+const SINGLE_BLANK_ONLY_ERROR: &str = "Only manhattan distance heuristic supports several blanks.";
+const SINGLE_BLANK_GAME_ERROR: &str = "Game supports only states with single blank.";
 
 fn main() {
     let cli = Cli::parse();
 
-    let used_heuristic: Box<dyn Heuristic<PUZZLE_SIZE>> = match cli.heuristic {
-        AvailableHeuristics::ManhattanDistance => Box::new(ManhattanDistance::new()),
-        AvailableHeuristics::DisjointDatabases => Box::new(DisjointDatabases::new(false)),
-    };
+    match cli.command {
+        Some(Command::CheckHeuristic(check_heuristic_args)) => {
+            check_heuristic(check_heuristic_args)
+        }
+        Some(Command::Generate(generate_args)) => generate(generate_args),
+        Some(Command::Play(play_args)) => play(play_args),
+        Some(Command::Analyze(AnalyzeCommand::Layers(layers_args))) => analyze_layers(layers_args),
+        None => solve(cli.solve_args),
+    }
+}
 
-    let initial_puzzle_state = if let Some(puzzle_state) = cli.puzzle_state {
-        puzzle_state
-            .parse::<PuzzleState<PUZZLE_SIZE>>()
-            // TODO: print appropriate errors
-            .expect("Couldn't parse puzzle state")
-    } else {
-        puzzle::generate_random_puzzle_state(MAX_STEPS_BACK)
-    };
+fn solve(solve_args: SolveArgs) {
+    let heuristic = solve_args
+        .heuristic
+        .expect("Heuristic is required when solving.");
+
+    let initial_puzzle_state = initial_puzzle_state::<PUZZLE_SIZE>(&solve_args.initial_state_args);
 
     println!("Initial puzzle state: {initial_puzzle_state}");
 
-    let solution = puzzle::solve_with_heuristic(initial_puzzle_state, &*used_heuristic);
+    let used_heuristic =
+        match build_heuristic(&heuristic, solve_args.reflection, &initial_puzzle_state) {
+            Ok(used_heuristic) => used_heuristic,
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        };
+
+    let progress_observer = solve_args.progress.map(ProgressObserver::new);
+    let trace_observer = match solve_args.trace.as_ref().map(JsonLinesObserver::create) {
+        Some(Ok(trace_observer)) => Some(trace_observer),
+        Some(Err(error)) => {
+            eprintln!("Couldn't create trace file: {error}");
+            return;
+        }
+        None => None,
+    };
+    let search_tree_recorder = solve_args
+        .dot
+        .as_ref()
+        .map(|_| SearchTreeRecorder::new(solve_args.dot_max_nodes));
+    let mut observer = ((progress_observer, trace_observer), search_tree_recorder);
+
+    let solution = puzzle::solve_with_observer(
+        initial_puzzle_state,
+        &*used_heuristic,
+        SearchOptions::new()
+            .with_tie_breaking(solve_args.tie_breaking.into())
+            .with_open_list(solve_args.open_list.into()),
+        &mut observer,
+    )
+    .ok();
+
+    let ((_, trace_observer), search_tree_recorder) = observer;
+
+    if let Some(trace_observer) = trace_observer {
+        if let Err(error) = trace_observer.finish() {
+            eprintln!("Couldn't write trace: {error}");
+        }
+    }
+
+    if let (Some(dot_path), Some(search_tree_recorder)) = (&solve_args.dot, search_tree_recorder) {
+        if let Err(error) = write_dot(dot_path, &search_tree_recorder) {
+            eprintln!("Couldn't write DOT file: {error}");
+        }
+    }
+
+    let several_blanks = initial_puzzle_state.blanks_count() > 1;
 
     if let Some(solution) = solution {
+        if several_blanks && (solve_args.animate || solve_args.step) {
+            eprintln!("Animation supports only states with single blank.");
+        } else if solve_args.animate || solve_args.step {
+            let playback = if solve_args.step {
+                Playback::KeyPress
+            } else {
+                Playback::Delay(Duration::from_millis(solve_args.delay))
+            };
+
+            if let Err(error) =
+                terminal::animate_solution(initial_puzzle_state, solution.steps(), playback)
+            {
+                eprintln!("Terminal error: {error}");
+            }
+        }
+
         let solution_steps = solution.steps();
         let no_of_visited_states = solution.no_of_visited_states();
 
         println!("Solution steps: {solution_steps:?}");
+        if several_blanks {
+            println!("Moved tiles: {:?}", solution.moved_tiles());
+        }
+
         println!("Solution len: {}", solution_steps.len());
         println!("Number of visited states: {no_of_visited_states:?}");
+
+        if solve_args.stats {
+            print_stats(solution.stats());
+        }
+
+        if let Some(stats_json_path) = &solve_args.stats_json {
+            if let Err(error) = fs::write(stats_json_path, solution.stats().to_json()) {
+                eprintln!("Couldn't write statistics: {error}");
+            }
+        }
+
+        if let Some(limit) = solve_args.all_optimal {
+            print_optimal_solutions(initial_puzzle_state, &*used_heuristic, limit);
+        }
     } else {
         println!("State unsolvable.");
     }
 }
+
+fn print_optimal_solutions(
+    initial_puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    limit: usize,
+) {
+    let optimal_solutions = optimal_solutions::find_optimal_solutions(
+        initial_puzzle_state,
+        heuristic,
+        SearchOptions::new(),
+    )
+    .expect("Solvable state has optimal solutions.");
+
+    println!("Number of optimal solutions: {}", optimal_solutions.count());
+    println!(
+        "States visited when counting: {}",
+        optimal_solutions.counted_states()
+    );
+
+    let several_blanks = initial_puzzle_state.blanks_count() > 1;
+
+    for solution in optimal_solutions.solutions(limit) {
+        let (steps, moved_tiles): (Vec<_>, Vec<_>) = solution.into_iter().unzip();
+
+        if several_blanks {
+            println!("{steps:?} moving tiles {moved_tiles:?}");
+        } else {
+            println!("{steps:?}");
+        }
+    }
+}
+
+fn write_dot(
+    path: &Path,
+    search_tree_recorder: &SearchTreeRecorder<PUZZLE_SIZE>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    search_tree_recorder.write_dot(&mut writer)?;
+    writer.flush()
+}
+
+fn print_stats(stats: &SearchStats) {
+    println!("Expanded states: {}", stats.expanded_states());
+    println!("Generated states: {}", stats.generated_states());
+    println!("Duplicate hits: {}", stats.duplicate_hits());
+    println!("Reopened states: {}", stats.reopened_states());
+    println!("Peak frontier size: {}", stats.peak_frontier_size());
+    println!("Heuristic evaluations: {}", stats.heuristic_evaluations());
+    println!(
+        "Initial heuristic value: {}",
+        stats.initial_heuristic_value()
+    );
+
+    if let Some(effective_branching_factor) = stats.effective_branching_factor() {
+        println!("Effective branching factor: {effective_branching_factor:.4}");
+    }
+
+    println!("Wall time: {:.3?}", stats.wall_time());
+    println!("Expanded states per f value:");
+
+    for (f_value, expanded_states) in stats.expanded_states_per_f_value() {
+        println!("  {f_value}: {expanded_states}");
+    }
+}
+
+/// Returns puzzle state given in arguments or generates random one.
+fn initial_puzzle_state<const SIZE: usize>(
+    initial_state_args: &InitialStateArgs,
+) -> PuzzleState<SIZE> {
+    let topology = initial_state_args.topology.into();
+
+    if let Some(puzzle_state) = &initial_state_args.puzzle_state {
+        puzzle_state
+            .parse::<PuzzleState<SIZE>>()
+            // TODO: print appropriate errors
+            .expect("Couldn't parse puzzle state")
+            .with_topology(topology)
+    } else {
+        let seed = initial_state_args
+            .seed
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        println!("Seed: {seed}");
+
+        match initial_state_args.generator {
+            Generator::Uniform => {
+                generator::uniform_random_puzzle_state_with_topology(topology, &mut rng)
+            }
+            Generator::Walk => {
+                let solved_state = PuzzleState::solved().with_topology(topology);
+
+                generator::random_walk_from(solved_state, MAX_STEPS_BACK, &mut rng)
+            }
+        }
+    }
+}
+
+fn check_heuristic(check_heuristic_args: CheckHeuristicArgs) {
+    let heuristic = &check_heuristic_args.heuristic;
+
+    match check_heuristic_args.size {
+        2 => match build_small_puzzle_heuristic(heuristic, &PuzzleState::<2>::solved()) {
+            Ok(heuristic) => print_report(&validate::validate_exhaustive(&*heuristic)),
+            Err(error) => eprintln!("{error}"),
+        },
+        3 => match build_small_puzzle_heuristic(heuristic, &PuzzleState::<3>::solved()) {
+            Ok(heuristic) => print_report(&validate::validate_exhaustive(&*heuristic)),
+            Err(error) => eprintln!("{error}"),
+        },
+        _ => {
+            let solved_state = PuzzleState::solved();
+            let used_heuristic =
+                build_heuristic(heuristic, check_heuristic_args.reflection, &solved_state);
+            let reference = build_heuristic(&check_heuristic_args.reference, false, &solved_state);
+
+            match (used_heuristic, reference) {
+                (Ok(used_heuristic), Ok(reference)) => print_report(&validate::validate_sampled(
+                    &*used_heuristic,
+                    &*reference,
+                    check_heuristic_args.samples,
+                    check_heuristic_args.steps_back,
+                    &mut rand::thread_rng(),
+                )),
+                (Err(error), _) | (_, Err(error)) => eprintln!("{error}"),
+            }
+        }
+    }
+}
+
+fn generate(generate_args: GenerateArgs) {
+    let seed = generate_args
+        .seed
+        .unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let distance = generate_args.distance;
+    let count = generate_args.count;
+    let max_distance = MAX_DISTANCES[generate_args.size as usize - 2];
+
+    if distance > max_distance {
+        eprintln!(
+            "Distance {distance} is not reachable, the hardest states of size {} are {max_distance} moves from solution.",
+            generate_args.size
+        );
+        return;
+    }
+
+    println!("# Seed: {seed}");
+
+    println!("# Distance: {distance}");
+
+    match generate_args.size {
+        2 => print_chosen_states(
+            &generator::puzzle_states_at_distance::<2>(distance as usize),
+            count,
+            &mut rng,
+        ),
+        3 => print_chosen_states(
+            &PerfectHeuristic::<3>::new().states_at_distance(distance),
+            count,
+            &mut rng,
+        ),
+        _ if distance <= MAX_LAYER_DISTANCE => print_chosen_states(
+            &generator::puzzle_states_at_distance::<PUZZLE_SIZE>(distance as usize),
+            count,
+            &mut rng,
+        ),
+        _ => {
+            let disjoint_databases = DisjointDatabases::new(false);
+            let mut puzzle_states = vec![];
+
+            for _ in 0..count {
+                match generator::sampled_puzzle_state_at_distance::<PUZZLE_SIZE, _>(
+                    distance as usize,
+                    &disjoint_databases,
+                    MAX_SAMPLING_ATTEMPTS,
+                    &mut rng,
+                ) {
+                    Some(puzzle_state) => puzzle_states.push(puzzle_state),
+                    None => {
+                        eprintln!(
+                            "Couldn't generate state with distance {distance} in {MAX_SAMPLING_ATTEMPTS} attempts."
+                        );
+                        break;
+                    }
+                }
+            }
+
+            print_batch(&puzzle_states);
+        }
+    }
+}
+
+/// Prints `count` randomly chosen states from `puzzle_states`.
+fn print_chosen_states<const SIZE: usize>(
+    puzzle_states: &[PuzzleState<SIZE>],
+    count: usize,
+    rng: &mut StdRng,
+) {
+    if puzzle_states.len() < count {
+        eprintln!(
+            "Number of states with given distance is only {}.",
+            puzzle_states.len()
+        );
+    }
+
+    let chosen_states: Vec<_> = puzzle_states.choose_multiple(rng, count).copied().collect();
+
+    print_batch(&chosen_states);
+}
+
+fn print_batch<const SIZE: usize>(puzzle_states: &[PuzzleState<SIZE>]) {
+    batch::write_batch(&mut std::io::stdout().lock(), puzzle_states)
+        .expect("Couldn't write generated states");
+}
+
+fn analyze_layers(layers_args: LayersArgs) {
+    let result = match layers_args.size {
+        2 => analyze_layers_of_size::<2>(&layers_args),
+        3 => analyze_layers_of_size::<3>(&layers_args),
+        _ => analyze_layers_of_size::<PUZZLE_SIZE>(&layers_args),
+    };
+
+    if let Err(error) = result {
+        eprintln!("Analysis failed: {error}");
+    }
+}
+
+fn analyze_layers_of_size<const SIZE: usize>(layers_args: &LayersArgs) -> io::Result<()> {
+    let (layer_sizes, examples, complete) = if let Some(directory) = &layers_args.external {
+        let mut external_bfs = ExternalBfs::new(directory);
+
+        if let Some(max_depth) = layers_args.max_depth {
+            external_bfs = external_bfs.with_max_distance(max_depth);
+        }
+
+        let mut layer_sizes = external_bfs.run::<SIZE>()?;
+        let complete = layer_sizes.last() == Some(&0);
+
+        if complete {
+            layer_sizes.pop();
+        }
+
+        let examples: Vec<_> = external_bfs
+            .read_layer(layer_sizes.len() - 1)?
+            .into_iter()
+            .take(layers_args.examples)
+            .filter_map(PuzzleState::<SIZE>::unrank)
+            .collect();
+
+        (layer_sizes, examples, complete)
+    } else {
+        if SIZE > MAX_PERFECT_PUZZLE_SIZE && layers_args.max_depth.is_none() {
+            eprintln!("Layers of size {SIZE} don't fit in memory, use --max-depth or --external.");
+            return Ok(());
+        }
+
+        let layer_analysis = analysis::analyze_layers::<SIZE>(layers_args.max_depth);
+        let layer_sizes = layer_analysis
+            .layer_sizes()
+            .iter()
+            .map(|&layer_size| layer_size as u64)
+            .collect();
+        let examples: Vec<_> = layer_analysis
+            .last_layer()
+            .iter()
+            .take(layers_args.examples)
+            .copied()
+            .collect();
+
+        (layer_sizes, examples, layer_analysis.is_complete())
+    };
+
+    print_layers(&layer_sizes, &examples, complete);
+
+    Ok(())
+}
+
+fn print_layers<const SIZE: usize>(
+    layer_sizes: &[u64],
+    examples: &[PuzzleState<SIZE>],
+    complete: bool,
+) {
+    let max_distance = layer_sizes.len() - 1;
+
+    println!("Distance  States");
+
+    for (distance, layer_size) in layer_sizes.iter().enumerate() {
+        println!("{distance:>8}  {layer_size}");
+    }
+
+    println!("Total states: {}", layer_sizes.iter().sum::<u64>());
+
+    if complete {
+        println!("Max distance: {max_distance}");
+        println!("Antipodal states:");
+    } else {
+        println!("Search stopped at distance {max_distance}");
+        println!("States at distance {max_distance}:");
+    }
+
+    for puzzle_state in examples {
+        println!("{puzzle_state}");
+    }
+}
+
+fn play(play_args: PlayArgs) {
+    let result = match play_args.size {
+        2 => play_small_puzzle::<2>(&play_args),
+        3 => play_small_puzzle::<3>(&play_args),
+        _ => {
+            let heuristic = play_args
+                .heuristic
+                .clone()
+                .unwrap_or(HeuristicSpec::Named(HeuristicName::DisjointDatabases));
+
+            let initial_state = initial_puzzle_state::<PUZZLE_SIZE>(&play_args.initial_state_args);
+
+            check_single_blank(&initial_state)
+                .and_then(|()| build_heuristic(&heuristic, false, &initial_state))
+                .map(|heuristic| {
+                    let mut game = Game::new(initial_state);
+
+                    terminal::play(&mut game, &*heuristic)
+                })
+        }
+    };
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => eprintln!("Terminal error: {error}"),
+        Err(error) => eprintln!("{error}"),
+    }
+}
+
+fn play_small_puzzle<const SIZE: usize>(
+    play_args: &PlayArgs,
+) -> Result<std::io::Result<()>, String> {
+    let heuristic = play_args
+        .heuristic
+        .clone()
+        .unwrap_or(HeuristicSpec::Named(HeuristicName::Perfect));
+
+    let initial_state = initial_puzzle_state::<SIZE>(&play_args.initial_state_args);
+
+    check_single_blank(&initial_state)
+        .and_then(|()| build_small_puzzle_heuristic(&heuristic, &initial_state))
+        .map(|heuristic| {
+            let mut game = Game::new(initial_state);
+
+            terminal::play(&mut game, &*heuristic)
+        })
+}
+
+/// Checks that game can be played from `initial_state`, player moves only single blank.
+fn check_single_blank<const SIZE: usize>(initial_state: &PuzzleState<SIZE>) -> Result<(), String> {
+    if initial_state.blanks_count() > 1 {
+        Err(SINGLE_BLANK_GAME_ERROR.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Builds heuristic for default puzzle size.
+fn build_heuristic(
+    heuristic: &HeuristicSpec,
+    reflection: bool,
+    initial_state: &PuzzleState<PUZZLE_SIZE>,
+) -> Result<Box<dyn Heuristic<PUZZLE_SIZE>>, String> {
+    let topology = initial_state.topology();
+    let several_blanks = initial_state.blanks_count() > 1;
+
+    heuristic.build::<PUZZLE_SIZE, String>(&mut |heuristic_name| match heuristic_name {
+        HeuristicName::ManhattanDistance => {
+            Ok(Box::new(ManhattanDistance::new().with_topology(topology)))
+        }
+        HeuristicName::DisjointDatabases if topology != Topology::Flat => {
+            Err(FLAT_TOPOLOGY_ONLY_ERROR.to_string())
+        }
+        HeuristicName::DisjointDatabases if several_blanks => {
+            Err(SINGLE_BLANK_ONLY_ERROR.to_string())
+        }
+        HeuristicName::DisjointDatabases => Ok(Box::new(
+            DisjointDatabases::new(false).with_reflection(reflection),
+        )),
+        HeuristicName::Perfect => Err(format!(
+            "Perfect heuristic works only for puzzle size up to {MAX_PERFECT_PUZZLE_SIZE}."
+        )),
+    })
+}
+
+/// Builds heuristic for small puzzle sizes (disjoint databases heuristic is not available).
+fn build_small_puzzle_heuristic<const SIZE: usize>(
+    heuristic: &HeuristicSpec,
+    initial_state: &PuzzleState<SIZE>,
+) -> Result<Box<dyn Heuristic<SIZE>>, String> {
+    let topology = initial_state.topology();
+    let several_blanks = initial_state.blanks_count() > 1;
+
+    heuristic.build::<SIZE, String>(&mut |heuristic_name| match heuristic_name {
+        HeuristicName::ManhattanDistance => {
+            Ok(Box::new(ManhattanDistance::new().with_topology(topology)))
+        }
+        HeuristicName::Perfect if topology != Topology::Flat => {
+            Err(FLAT_TOPOLOGY_ONLY_ERROR.to_string())
+        }
+        HeuristicName::Perfect if several_blanks => Err(SINGLE_BLANK_ONLY_ERROR.to_string()),
+        HeuristicName::DisjointDatabases => Err(format!(
+            "Disjoint databases heuristic works only for puzzle size {PUZZLE_SIZE}."
+        )),
+        HeuristicName::Perfect => Ok(Box::new(PerfectHeuristic::new())),
+    })
+}
+
+fn print_report<const SIZE: usize>(report: &ValidationReport<SIZE>) {
+    let inadmissible_states = report.inadmissible_states();
+    let inconsistent_moves = report.inconsistent_moves();
+
+    println!("Checked states: {}", report.checked_states());
+    println!("Inadmissible states: {}", inadmissible_states.len());
+
+    for inadmissible_state in inadmissible_states.iter().take(MAX_REPORTED_ERRORS) {
+        println!(
+            "  {}: heuristic {} > distance {}",
+            inadmissible_state.puzzle_state,
+            inadmissible_state.heuristic_value,
+            inadmissible_state.distance
+        );
+    }
+
+    println!("Inconsistent moves: {}", inconsistent_moves.len());
+
+    for inconsistent_move in inconsistent_moves.iter().take(MAX_REPORTED_ERRORS) {
+        println!(
+            "  {} -> {}: heuristic {} -> {}",
+            inconsistent_move.from,
+            inconsistent_move.to,
+            inconsistent_move.from_heuristic_value,
+            inconsistent_move.to_heuristic_value
+        );
+    }
+
+    if let Some(average_ratio) = report.average_ratio() {
+        println!("Average heuristic / distance ratio: {average_ratio:.3}");
+    }
+}
@@ -0,0 +1,417 @@
+/*!
+* Hooks into A* search for visualising and debugging it.
+* Solver is generic over observer, so [NoObserver] callbacks are compiled out.
+*/
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::astar_state::AstarState;
+use crate::{Direction, PuzzleState};
+
+/// Callbacks called by A* search. All of them do nothing by default.
+pub trait SearchObserver<const PUZZLE_SIZE: usize> {
+    /// Called when state is taken from frontier and its neighbours are going to be generated.
+    fn on_expand(&mut self, _astar_state: &AstarState<PUZZLE_SIZE>) {}
+
+    /// Called when new state is pushed to frontier.
+    fn on_generate(&mut self, _astar_state: &AstarState<PUZZLE_SIZE>) {}
+
+    /// Called when already expanded state is generated or taken from frontier.
+    fn on_duplicate(&mut self, _puzzle_state: PuzzleState<PUZZLE_SIZE>) {}
+
+    /// Called when expanded state has higher f value than all previously expanded states.
+    fn on_new_f_bound(&mut self, _f_bound: u8) {}
+
+    /// Called when solution is found, `moved_tiles` are numbers of tiles moved by `steps`.
+    fn on_solution(&mut self, _steps: &[Direction], _moved_tiles: &[u8]) {}
+}
+
+/// Observer ignoring all events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoObserver;
+
+impl<const PUZZLE_SIZE: usize> SearchObserver<PUZZLE_SIZE> for NoObserver {}
+
+impl<const PUZZLE_SIZE: usize, O: SearchObserver<PUZZLE_SIZE>> SearchObserver<PUZZLE_SIZE>
+    for Option<O>
+{
+    fn on_expand(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        if let Some(observer) = self {
+            observer.on_expand(astar_state);
+        }
+    }
+
+    fn on_generate(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        if let Some(observer) = self {
+            observer.on_generate(astar_state);
+        }
+    }
+
+    fn on_duplicate(&mut self, puzzle_state: PuzzleState<PUZZLE_SIZE>) {
+        if let Some(observer) = self {
+            observer.on_duplicate(puzzle_state);
+        }
+    }
+
+    fn on_new_f_bound(&mut self, f_bound: u8) {
+        if let Some(observer) = self {
+            observer.on_new_f_bound(f_bound);
+        }
+    }
+
+    fn on_solution(&mut self, steps: &[Direction], moved_tiles: &[u8]) {
+        if let Some(observer) = self {
+            observer.on_solution(steps, moved_tiles);
+        }
+    }
+}
+
+/// Pair of observers is notified about events one after another.
+impl<const PUZZLE_SIZE: usize, A, B> SearchObserver<PUZZLE_SIZE> for (A, B)
+where
+    A: SearchObserver<PUZZLE_SIZE>,
+    B: SearchObserver<PUZZLE_SIZE>,
+{
+    fn on_expand(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.0.on_expand(astar_state);
+        self.1.on_expand(astar_state);
+    }
+
+    fn on_generate(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.0.on_generate(astar_state);
+        self.1.on_generate(astar_state);
+    }
+
+    fn on_duplicate(&mut self, puzzle_state: PuzzleState<PUZZLE_SIZE>) {
+        self.0.on_duplicate(puzzle_state);
+        self.1.on_duplicate(puzzle_state);
+    }
+
+    fn on_new_f_bound(&mut self, f_bound: u8) {
+        self.0.on_new_f_bound(f_bound);
+        self.1.on_new_f_bound(f_bound);
+    }
+
+    fn on_solution(&mut self, steps: &[Direction], moved_tiles: &[u8]) {
+        self.0.on_solution(steps, moved_tiles);
+        self.1.on_solution(steps, moved_tiles);
+    }
+}
+
+/// Observer counting events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountingObserver {
+    /// Number of expanded states.
+    pub expanded: usize,
+    /// Number of generated states.
+    pub generated: usize,
+    /// Number of duplicate hits.
+    pub duplicates: usize,
+    /// Number of f bound increases.
+    pub f_bounds: usize,
+    /// Number of found solutions.
+    pub solutions: usize,
+}
+
+impl CountingObserver {
+    /// Creates new instance of [CountingObserver] with zero counts.
+    pub fn new() -> Self {
+        CountingObserver::default()
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> SearchObserver<PUZZLE_SIZE> for CountingObserver {
+    fn on_expand(&mut self, _astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.expanded += 1;
+    }
+
+    fn on_generate(&mut self, _astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.generated += 1;
+    }
+
+    fn on_duplicate(&mut self, _puzzle_state: PuzzleState<PUZZLE_SIZE>) {
+        self.duplicates += 1;
+    }
+
+    fn on_new_f_bound(&mut self, _f_bound: u8) {
+        self.f_bounds += 1;
+    }
+
+    fn on_solution(&mut self, _steps: &[Direction], _moved_tiles: &[u8]) {
+        self.solutions += 1;
+    }
+}
+
+/// Observer printing progress every `interval` expansions and when f bound increases.
+#[derive(Debug)]
+pub struct ProgressObserver<W: Write> {
+    writer: W,
+    interval: usize,
+    expanded: usize,
+    f_bound: u8,
+}
+
+impl ProgressObserver<io::Stderr> {
+    /// Creates new instance of [ProgressObserver] printing to standard error output.
+    pub fn new(interval: usize) -> Self {
+        ProgressObserver::with_writer(io::stderr(), interval)
+    }
+}
+
+impl<W: Write> ProgressObserver<W> {
+    /// Creates new instance of [ProgressObserver] printing to `writer`.
+    pub fn with_writer(writer: W, interval: usize) -> Self {
+        ProgressObserver {
+            writer,
+            interval: interval.max(1),
+            expanded: 0,
+            f_bound: 0,
+        }
+    }
+
+    /// Returns inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<const PUZZLE_SIZE: usize, W: Write> SearchObserver<PUZZLE_SIZE> for ProgressObserver<W> {
+    fn on_expand(&mut self, _astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.expanded += 1;
+
+        if self.expanded.is_multiple_of(self.interval) {
+            // Progress is only informative, so write errors are ignored.
+            let _ = writeln!(
+                self.writer,
+                "Expanded states: {}, f bound: {}",
+                self.expanded, self.f_bound
+            );
+        }
+    }
+
+    fn on_new_f_bound(&mut self, f_bound: u8) {
+        self.f_bound = f_bound;
+
+        let _ = writeln!(self.writer, "New f bound: {f_bound}");
+    }
+
+    fn on_solution(&mut self, steps: &[Direction], _moved_tiles: &[u8]) {
+        let _ = writeln!(
+            self.writer,
+            "Solution of length {} found after expanding {} states",
+            steps.len(),
+            self.expanded
+        );
+    }
+}
+
+/**
+* Observer writing every event as one JSON object per line. First write error is kept and
+* returned by [JsonLinesObserver::finish], later events are ignored.
+*/
+#[derive(Debug)]
+pub struct JsonLinesObserver<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl JsonLinesObserver<BufWriter<File>> {
+    /// Creates new instance of [JsonLinesObserver] writing to file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(JsonLinesObserver::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> JsonLinesObserver<W> {
+    /// Creates new instance of [JsonLinesObserver] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        JsonLinesObserver {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes written events and returns inner writer or first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_event(&mut self, event: serde_json::Value) {
+        if self.error.is_none() {
+            if let Err(error) = writeln!(self.writer, "{event}") {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize, W: Write> SearchObserver<PUZZLE_SIZE> for JsonLinesObserver<W> {
+    fn on_expand(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.write_event(astar_state_event("expand", astar_state));
+    }
+
+    fn on_generate(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        self.write_event(astar_state_event("generate", astar_state));
+    }
+
+    fn on_duplicate(&mut self, puzzle_state: PuzzleState<PUZZLE_SIZE>) {
+        self.write_event(json!({
+            "event": "duplicate",
+            "state": puzzle_state.to_string(),
+        }));
+    }
+
+    fn on_new_f_bound(&mut self, f_bound: u8) {
+        self.write_event(json!({
+            "event": "new_f_bound",
+            "f": f_bound,
+        }));
+    }
+
+    fn on_solution(&mut self, steps: &[Direction], _moved_tiles: &[u8]) {
+        let steps: Vec<_> = steps.iter().map(|step| format!("{step:?}")).collect();
+
+        self.write_event(json!({
+            "event": "solution",
+            "length": steps.len(),
+            "steps": steps,
+        }));
+    }
+}
+
+fn astar_state_event<const PUZZLE_SIZE: usize>(
+    event: &str,
+    astar_state: &AstarState<PUZZLE_SIZE>,
+) -> serde_json::Value {
+    json!({
+        "event": event,
+        "state": astar_state.puzzle_state().to_string(),
+        "direction": astar_state.last_direction().map(|direction| format!("{direction:?}")),
+        "g": astar_state.distance_from_start(),
+        "h": astar_state.heuristic_value(),
+        "f": astar_state.f_value(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::ManhattanDistance;
+    use crate::{solve_with_observer, SearchOptions};
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    fn puzzle_state() -> PuzzleState<BIGGER_PUZZLE_SIZE> {
+        PuzzleState::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(6)],
+            [Some(7), Some(5), Some(8)],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn counting_observer_matches_stats() {
+        let mut observer = CountingObserver::new();
+
+        let solution = solve_with_observer(
+            puzzle_state(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut observer,
+        )
+        .unwrap();
+        let stats = solution.stats();
+
+        assert_eq!(stats.expanded_states(), observer.expanded);
+        assert_eq!(stats.generated_states(), observer.generated);
+        assert_eq!(stats.duplicate_hits(), observer.duplicates);
+        assert_eq!(1, observer.f_bounds);
+        assert_eq!(1, observer.solutions);
+    }
+
+    #[test]
+    fn pair_of_observers_works() {
+        let mut observers = (CountingObserver::new(), Some(CountingObserver::new()));
+
+        solve_with_observer(
+            puzzle_state(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut observers,
+        )
+        .unwrap();
+
+        assert_eq!(Some(observers.0), observers.1);
+    }
+
+    #[test]
+    fn progress_observer_works() {
+        let mut observer = ProgressObserver::with_writer(vec![], 1);
+
+        solve_with_observer(
+            puzzle_state(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut observer,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(observer.into_inner()).unwrap();
+
+        assert_eq!(
+            "New f bound: 2\n\
+             Expanded states: 1, f bound: 2\n\
+             Expanded states: 2, f bound: 2\n\
+             Expanded states: 3, f bound: 2\n\
+             Solution of length 2 found after expanding 3 states\n",
+            output
+        );
+    }
+
+    #[test]
+    fn json_lines_observer_works() {
+        let mut observer = JsonLinesObserver::new(vec![]);
+
+        solve_with_observer(
+            puzzle_state(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut observer,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(observer.finish().unwrap()).unwrap();
+        let events: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!("new_f_bound", events[0]["event"]);
+        assert_eq!("expand", events[1]["event"]);
+        assert_eq!("[1, 2, 3, 4, , 6, 7, 5, 8]", events[1]["state"]);
+        assert_eq!(0, events[1]["g"]);
+        assert_eq!(2, events[1]["h"]);
+        assert!(events[1]["direction"].is_null());
+
+        let solution_event = events.last().unwrap();
+
+        assert_eq!("solution", solution_event["event"]);
+        assert_eq!(2, solution_event["length"]);
+        assert_eq!(
+            serde_json::json!(["Down", "Right"]),
+            solution_event["steps"]
+        );
+    }
+}
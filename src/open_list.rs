@@ -0,0 +1,343 @@
+/*!
+* Open list (frontier) of A* search. States with lowest f value are taken first, ties are broken
+* according to [TieBreaking] and finally by insertion order, so search is deterministic.
+* Both implementations take states in the same order.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::astar_state::AstarState;
+
+/// Frontier of A* search.
+pub trait OpenList<const PUZZLE_SIZE: usize> {
+    /// Adds state to open list.
+    fn push(&mut self, astar_state: AstarState<PUZZLE_SIZE>);
+
+    /// Removes and returns state which should be expanded next.
+    fn pop(&mut self) -> Option<AstarState<PUZZLE_SIZE>>;
+
+    /// Returns number of states in open list.
+    fn len(&self) -> usize;
+
+    /// Checks if open list is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Open list implementations available in [SearchOptions](crate::SearchOptions).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenListKind {
+    /// [HeapOpenList].
+    #[default]
+    Heap,
+    /// [BucketOpenList].
+    Buckets,
+}
+
+/// Order of states with the same f value taken from open list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreaking {
+    /**
+     * Prefer states with higher g value (deeper ones), then last inserted.
+     * Within the same f value it is the same as preferring lower h value.
+     */
+    #[default]
+    HigherG,
+    /// Prefer last inserted states.
+    Lifo,
+    /// Prefer first inserted states.
+    Fifo,
+}
+
+/// Priority of state in open list, smaller is taken first.
+type Priority = (u8, u8, u64);
+
+/// Open list entry ordered only by its priority.
+#[derive(Debug, Clone)]
+struct OpenListEntry<const PUZZLE_SIZE: usize> {
+    priority: Priority,
+    astar_state: AstarState<PUZZLE_SIZE>,
+}
+
+impl<const PUZZLE_SIZE: usize> PartialEq for OpenListEntry<PUZZLE_SIZE> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Eq for OpenListEntry<PUZZLE_SIZE> {}
+
+impl<const PUZZLE_SIZE: usize> PartialOrd for OpenListEntry<PUZZLE_SIZE> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Ord for OpenListEntry<PUZZLE_SIZE> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+/// Open list backed by binary heap.
+#[derive(Debug, Clone)]
+pub struct HeapOpenList<const PUZZLE_SIZE: usize> {
+    tie_breaking: TieBreaking,
+    pushed_states: u64,
+    heap: BinaryHeap<Reverse<OpenListEntry<PUZZLE_SIZE>>>,
+}
+
+impl<const PUZZLE_SIZE: usize> HeapOpenList<PUZZLE_SIZE> {
+    /// Creates empty [HeapOpenList] breaking ties with `tie_breaking`.
+    pub fn new(tie_breaking: TieBreaking) -> Self {
+        HeapOpenList {
+            tie_breaking,
+            pushed_states: 0,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    fn priority(&self, astar_state: &AstarState<PUZZLE_SIZE>) -> Priority {
+        let insertion_order = self.pushed_states;
+        let newer_first = u64::MAX - insertion_order;
+
+        match self.tie_breaking {
+            TieBreaking::HigherG => (
+                astar_state.f_value(),
+                u8::MAX - astar_state.distance_from_start(),
+                newer_first,
+            ),
+            TieBreaking::Lifo => (astar_state.f_value(), 0, newer_first),
+            TieBreaking::Fifo => (astar_state.f_value(), 0, insertion_order),
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> OpenList<PUZZLE_SIZE> for HeapOpenList<PUZZLE_SIZE> {
+    fn push(&mut self, astar_state: AstarState<PUZZLE_SIZE>) {
+        let priority = self.priority(&astar_state);
+
+        self.pushed_states += 1;
+        self.heap.push(Reverse(OpenListEntry {
+            priority,
+            astar_state,
+        }));
+    }
+
+    fn pop(&mut self) -> Option<AstarState<PUZZLE_SIZE>> {
+        self.heap.pop().map(|Reverse(entry)| entry.astar_state)
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+/// States with the same f value, split by g value when [TieBreaking::HigherG] is used.
+#[derive(Debug, Clone)]
+struct Bucket<const PUZZLE_SIZE: usize> {
+    queues: Vec<VecDeque<AstarState<PUZZLE_SIZE>>>,
+    len: usize,
+    /// Upper bound of highest index of non-empty queue.
+    max_queue_index: usize,
+}
+
+impl<const PUZZLE_SIZE: usize> Bucket<PUZZLE_SIZE> {
+    fn new() -> Self {
+        Bucket {
+            queues: vec![],
+            len: 0,
+            max_queue_index: 0,
+        }
+    }
+}
+
+/**
+* Open list with array of buckets indexed by f value (and by g value within bucket), so push and
+* pop take constant (amortized) time. It relies on f values being small integers.
+*/
+#[derive(Debug, Clone)]
+pub struct BucketOpenList<const PUZZLE_SIZE: usize> {
+    tie_breaking: TieBreaking,
+    buckets: Vec<Bucket<PUZZLE_SIZE>>,
+    len: usize,
+    /// Lower bound of lowest f value in open list.
+    min_f_value: usize,
+}
+
+impl<const PUZZLE_SIZE: usize> BucketOpenList<PUZZLE_SIZE> {
+    /// Creates empty [BucketOpenList] breaking ties with `tie_breaking`.
+    pub fn new(tie_breaking: TieBreaking) -> Self {
+        BucketOpenList {
+            tie_breaking,
+            buckets: vec![],
+            len: 0,
+            min_f_value: 0,
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> OpenList<PUZZLE_SIZE> for BucketOpenList<PUZZLE_SIZE> {
+    fn push(&mut self, astar_state: AstarState<PUZZLE_SIZE>) {
+        let f_value = astar_state.f_value() as usize;
+        let queue_index = match self.tie_breaking {
+            TieBreaking::HigherG => astar_state.distance_from_start() as usize,
+            TieBreaking::Lifo | TieBreaking::Fifo => 0,
+        };
+
+        if self.buckets.len() <= f_value {
+            self.buckets.resize_with(f_value + 1, Bucket::new);
+        }
+
+        let bucket = &mut self.buckets[f_value];
+
+        if bucket.queues.len() <= queue_index {
+            bucket.queues.resize_with(queue_index + 1, VecDeque::new);
+        }
+
+        bucket.queues[queue_index].push_back(astar_state);
+        bucket.len += 1;
+        bucket.max_queue_index = bucket.max_queue_index.max(queue_index);
+
+        if self.len == 0 || f_value < self.min_f_value {
+            self.min_f_value = f_value;
+        }
+
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<AstarState<PUZZLE_SIZE>> {
+        if self.len == 0 {
+            return None;
+        }
+
+        while self.buckets[self.min_f_value].len == 0 {
+            self.min_f_value += 1;
+        }
+
+        let bucket = &mut self.buckets[self.min_f_value];
+
+        while bucket.queues[bucket.max_queue_index].is_empty() {
+            bucket.max_queue_index -= 1;
+        }
+
+        let queue = &mut bucket.queues[bucket.max_queue_index];
+        let astar_state = match self.tie_breaking {
+            TieBreaking::HigherG | TieBreaking::Lifo => queue.pop_back(),
+            TieBreaking::Fifo => queue.pop_front(),
+        };
+
+        bucket.len -= 1;
+        self.len -= 1;
+
+        astar_state
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::ManhattanDistance;
+    use crate::puzzle_state::PuzzleState;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    /// Returns initial state followed by its neighbours.
+    fn astar_states() -> Vec<AstarState<BIGGER_PUZZLE_SIZE>> {
+        let manhattan_distance = ManhattanDistance::new();
+        let puzzle_state = PuzzleState::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(6)],
+            [Some(7), Some(5), Some(8)],
+        ])
+        .unwrap();
+        let initial_state = AstarState::inital(puzzle_state, &manhattan_distance).unwrap();
+        let mut astar_states = vec![initial_state.clone()];
+
+        for neighbour in initial_state.neighbours() {
+            astar_states.push(initial_state.moved_to_neighbour(neighbour, &manhattan_distance));
+        }
+
+        astar_states
+    }
+
+    fn popped_states(tie_breaking: TieBreaking) -> Vec<(u8, u8)> {
+        let heap_popped_states = popped_states_from(HeapOpenList::new(tie_breaking));
+        let bucket_popped_states = popped_states_from(BucketOpenList::new(tie_breaking));
+
+        assert_eq!(heap_popped_states, bucket_popped_states);
+
+        heap_popped_states
+    }
+
+    fn popped_states_from(mut open_list: impl OpenList<BIGGER_PUZZLE_SIZE>) -> Vec<(u8, u8)> {
+        for astar_state in astar_states() {
+            open_list.push(astar_state);
+        }
+
+        assert_eq!(5, open_list.len());
+
+        let mut popped_states = vec![];
+
+        while let Some(astar_state) = open_list.pop() {
+            popped_states.push((astar_state.f_value(), astar_state.distance_from_start()));
+        }
+
+        assert!(open_list.is_empty());
+
+        popped_states
+    }
+
+    #[test]
+    fn lowest_f_value_is_first() {
+        for tie_breaking in [TieBreaking::HigherG, TieBreaking::Lifo, TieBreaking::Fifo] {
+            let f_values: Vec<_> = popped_states(tie_breaking)
+                .into_iter()
+                .map(|(f_value, _)| f_value)
+                .collect();
+
+            assert_eq!(vec![2, 2, 4, 4, 4], f_values);
+        }
+    }
+
+    #[test]
+    fn tie_breaking_works() {
+        // Initial state has g = 0 and was inserted first.
+        assert_eq!((2, 1), popped_states(TieBreaking::HigherG)[0]);
+        assert_eq!((2, 0), popped_states(TieBreaking::HigherG)[1]);
+        assert_eq!((2, 1), popped_states(TieBreaking::Lifo)[0]);
+        assert_eq!((2, 0), popped_states(TieBreaking::Fifo)[0]);
+    }
+
+    #[test]
+    fn bucket_open_list_handles_interleaved_operations() {
+        let mut open_list = BucketOpenList::new(TieBreaking::HigherG);
+        let astar_states = astar_states();
+        let deeper_state = astar_states
+            .iter()
+            .find(|state| state.f_value() == 2 && state.distance_from_start() == 1)
+            .unwrap();
+        let worse_state = astar_states
+            .iter()
+            .find(|state| state.f_value() == 4)
+            .unwrap();
+
+        open_list.push(worse_state.clone());
+        open_list.push(deeper_state.clone());
+
+        assert_eq!(Some(2), open_list.pop().map(|state| state.f_value()));
+
+        open_list.push(astar_states[0].clone());
+
+        assert_eq!(Some(2), open_list.pop().map(|state| state.f_value()));
+        assert_eq!(Some(4), open_list.pop().map(|state| state.f_value()));
+        assert_eq!(None, open_list.pop().map(|state| state.f_value()));
+    }
+}
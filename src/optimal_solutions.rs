@@ -0,0 +1,447 @@
+/*!
+* Enumeration of all optimal solutions. Length of optimal solution is found by A* first, then
+* depth-first search pruned by admissible heuristic counts paths of that length from every
+* visited state. Counts are memoized, so they form a graph of optimal moves, which is walked
+* lazily when solutions are listed (their number can grow exponentially with length).
+* Every step of listed solution is recorded with number of moved tile, as direction alone doesn't
+* tell which blank moved in states with several blanks.
+*/
+
+use std::collections::HashMap;
+
+use crate::heuristics::Heuristic;
+use crate::puzzle_state::puzzle_move::Neighbours;
+use crate::{solve_with_options, Direction, PuzzleState, SearchError, SearchOptions, SearchStats};
+
+/// All optimal solutions of puzzle state.
+#[derive(Debug, Clone)]
+pub struct OptimalSolutions<const PUZZLE_SIZE: usize> {
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    solution_length: u8,
+    stats: SearchStats,
+    /// Number of optimal paths to solution from state reached after given number of moves.
+    counts: HashMap<(PuzzleState<PUZZLE_SIZE>, u8), u64>,
+}
+
+impl<const PUZZLE_SIZE: usize> OptimalSolutions<PUZZLE_SIZE> {
+    /// Accessor for `solution_length` field.
+    pub fn solution_length(&self) -> usize {
+        self.solution_length as usize
+    }
+
+    /// Returns statistics of A* search which found optimal solution length.
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Returns number of states visited when counting optimal paths.
+    pub fn counted_states(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns number of optimal solutions (saturated at `u64::MAX`).
+    pub fn count(&self) -> u64 {
+        self.path_count(self.initial_state, 0)
+    }
+
+    /**
+     * Returns lazy iterator over all optimal solutions in order of [PuzzleState::neighbours] (for
+     * single blank it is lexicographic order of directions).
+     */
+    pub fn iter(&self) -> OptimalSolutionsIter<'_, PUZZLE_SIZE> {
+        OptimalSolutionsIter {
+            optimal_solutions: self,
+            path: vec![],
+            neighbours: vec![self.initial_state.neighbours()],
+            finished: self.count() == 0,
+        }
+    }
+
+    /// Returns at most `limit` optimal solutions as directions with numbers of moved tiles.
+    pub fn solutions(&self, limit: usize) -> Vec<Vec<(Direction, u8)>> {
+        self.iter().take(limit).collect()
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> OptimalSolutions<PUZZLE_SIZE> {
+    fn path_count(&self, puzzle_state: PuzzleState<PUZZLE_SIZE>, distance: u8) -> u64 {
+        self.counts
+            .get(&(puzzle_state, distance))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl<'a, const PUZZLE_SIZE: usize> IntoIterator for &'a OptimalSolutions<PUZZLE_SIZE> {
+    type Item = Vec<(Direction, u8)>;
+    type IntoIter = OptimalSolutionsIter<'a, PUZZLE_SIZE>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over optimal solutions created by [OptimalSolutions::iter].
+#[derive(Debug, Clone)]
+pub struct OptimalSolutionsIter<'a, const PUZZLE_SIZE: usize> {
+    optimal_solutions: &'a OptimalSolutions<PUZZLE_SIZE>,
+    path: Vec<(Direction, u8)>,
+    /// Remaining moves to try at every depth.
+    neighbours: Vec<Neighbours<PUZZLE_SIZE>>,
+    finished: bool,
+}
+
+impl<const PUZZLE_SIZE: usize> Iterator for OptimalSolutionsIter<'_, PUZZLE_SIZE> {
+    type Item = Vec<(Direction, u8)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let solution_length = self.optimal_solutions.solution_length;
+
+        loop {
+            if self.path.len() == solution_length as usize {
+                let solution = self.path.clone();
+
+                self.backtrack();
+
+                return Some(solution);
+            }
+
+            let depth = self.path.len();
+            let Some(neighbour) = self.neighbours[depth].next() else {
+                self.backtrack();
+
+                if self.finished {
+                    return None;
+                }
+
+                continue;
+            };
+
+            let moved_tile = neighbour.moved_tile();
+            let (direction, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            if self
+                .optimal_solutions
+                .path_count(neighbour_state, depth as u8 + 1)
+                > 0
+            {
+                self.path.push((direction, moved_tile));
+                self.neighbours.push(neighbour_state.neighbours());
+            }
+        }
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> OptimalSolutionsIter<'_, PUZZLE_SIZE> {
+    /// Returns to previous state on path, finishes iteration when there is none.
+    fn backtrack(&mut self) {
+        if self.path.pop().is_none() {
+            self.finished = true;
+            return;
+        }
+
+        self.neighbours.pop();
+    }
+}
+
+/**
+* Finds all optimal solutions of `initial_state` using admissible `heuristic`. `options` limit
+* A* search finding optimal solution length.
+*/
+pub fn find_optimal_solutions<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    options: SearchOptions,
+) -> Result<OptimalSolutions<PUZZLE_SIZE>, SearchError> {
+    let solution = solve_with_options(initial_state, heuristic, options)?;
+    let mut optimal_solutions = OptimalSolutions {
+        initial_state,
+        solution_length: solution.steps().len() as u8,
+        stats: solution.stats().clone(),
+        counts: HashMap::new(),
+    };
+
+    count_paths(&mut optimal_solutions, heuristic, initial_state, 0);
+
+    Ok(optimal_solutions)
+}
+
+/// Counts (and memoizes) optimal paths to solution from `puzzle_state` after `distance` moves.
+fn count_paths<const PUZZLE_SIZE: usize>(
+    optimal_solutions: &mut OptimalSolutions<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    distance: u8,
+) -> u64 {
+    if let Some(&count) = optimal_solutions.counts.get(&(puzzle_state, distance)) {
+        return count;
+    }
+
+    let solution_length = optimal_solutions.solution_length;
+    let count = if distance == solution_length {
+        u64::from(puzzle_state.is_solved())
+    } else if distance + puzzle_state.calculate_heuristic(heuristic) > solution_length {
+        0
+    } else {
+        puzzle_state.neighbours().fold(0_u64, |count, neighbour| {
+            let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            count.saturating_add(count_paths(
+                optimal_solutions,
+                heuristic,
+                neighbour_state,
+                distance + 1,
+            ))
+        })
+    };
+
+    optimal_solutions
+        .counts
+        .insert((puzzle_state, distance), count);
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::generator::uniform_random_puzzle_state;
+    use crate::heuristics::ManhattanDistance;
+    use crate::puzzle_state::puzzle_move::DIRECTIONS;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const BIGGEST_PUZZLE_SIZE: usize = 4;
+
+    const SEED: u64 = 42;
+
+    /// Applies `steps` (moving given tiles) to `puzzle_state` and checks that result is solved.
+    fn is_solution<const SIZE: usize>(
+        puzzle_state: PuzzleState<SIZE>,
+        steps: &[(Direction, u8)],
+    ) -> bool {
+        steps
+            .iter()
+            .try_fold(puzzle_state, |puzzle_state, &(direction, moved_tile)| {
+                puzzle_state.create_tile_move_state(moved_tile, direction)
+            })
+            .is_some_and(|puzzle_state| puzzle_state.is_solved())
+    }
+
+    /// Drops numbers of moved tiles from `solutions`.
+    fn directions(solutions: Vec<Vec<(Direction, u8)>>) -> Vec<Vec<Direction>> {
+        solutions
+            .into_iter()
+            .map(|steps| steps.into_iter().map(|(direction, _)| direction).collect())
+            .collect()
+    }
+
+    #[test]
+    fn single_optimal_solution_is_found() {
+        let puzzle_state = PuzzleState::<BIGGEST_PUZZLE_SIZE>::new([
+            [None, Some(2), Some(3), Some(4)],
+            [Some(1), Some(6), Some(7), Some(8)],
+            [Some(5), Some(10), Some(11), Some(12)],
+            [Some(9), Some(13), Some(14), Some(15)],
+        ])
+        .unwrap();
+
+        let optimal_solutions = find_optimal_solutions(
+            puzzle_state,
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+        )
+        .unwrap();
+
+        assert_eq!(6, optimal_solutions.solution_length());
+        assert_eq!(1, optimal_solutions.count());
+        assert_eq!(6, optimal_solutions.stats().solution_length());
+        assert!(optimal_solutions.counted_states() > 6);
+        assert_eq!(
+            vec![vec![
+                Direction::Down,
+                Direction::Down,
+                Direction::Down,
+                Direction::Right,
+                Direction::Right,
+                Direction::Right
+            ]],
+            directions(optimal_solutions.solutions(10))
+        );
+    }
+
+    #[test]
+    fn both_ways_around_small_puzzle_are_found() {
+        // The only state at distance 6, reachable by going around board in both directions.
+        let puzzle_state = "[, 3, 2, 1]".parse::<PuzzleState<PUZZLE_SIZE>>().unwrap();
+
+        let optimal_solutions = find_optimal_solutions(
+            puzzle_state,
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+        )
+        .unwrap();
+
+        assert_eq!(2, optimal_solutions.count());
+        assert_eq!(
+            vec![
+                vec![
+                    Direction::Down,
+                    Direction::Right,
+                    Direction::Up,
+                    Direction::Left,
+                    Direction::Down,
+                    Direction::Right
+                ],
+                vec![
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Up,
+                    Direction::Right,
+                    Direction::Down
+                ]
+            ],
+            directions(optimal_solutions.solutions(10))
+        );
+        assert_eq!(1, optimal_solutions.solutions(1).len());
+    }
+
+    #[test]
+    fn solved_state_has_one_empty_solution() {
+        let optimal_solutions = find_optimal_solutions(
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+        )
+        .unwrap();
+
+        assert_eq!(1, optimal_solutions.count());
+        assert_eq!(
+            vec![Vec::<Direction>::new()],
+            directions(optimal_solutions.solutions(10))
+        );
+    }
+
+    #[test]
+    fn unsolvable_state_is_reported() {
+        let puzzle_state = "[2, 1, 3, ]".parse::<PuzzleState<PUZZLE_SIZE>>().unwrap();
+
+        assert_eq!(
+            SearchError::NotSolvable,
+            find_optimal_solutions(
+                puzzle_state,
+                &ManhattanDistance::new(),
+                SearchOptions::new()
+            )
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn counts_match_breadth_first_search() {
+        // Number of shortest paths from every state to solved state.
+        let mut path_counts =
+            HashMap::from([(PuzzleState::<BIGGER_PUZZLE_SIZE>::solved(), (0, 1))]);
+        let mut queue = VecDeque::from([PuzzleState::<BIGGER_PUZZLE_SIZE>::solved()]);
+
+        while let Some(puzzle_state) = queue.pop_front() {
+            let (distance, count) = path_counts[&puzzle_state];
+
+            for neighbour in puzzle_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+                let (neighbour_distance, neighbour_count) =
+                    path_counts.entry(neighbour_state).or_insert_with(|| {
+                        queue.push_back(neighbour_state);
+                        (distance + 1, 0)
+                    });
+
+                if *neighbour_distance == distance + 1 {
+                    *neighbour_count += count;
+                }
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        for _ in 0..20 {
+            let puzzle_state = uniform_random_puzzle_state::<BIGGER_PUZZLE_SIZE, _>(&mut rng);
+            let optimal_solutions = find_optimal_solutions(
+                puzzle_state,
+                &ManhattanDistance::new(),
+                SearchOptions::new(),
+            )
+            .unwrap();
+            let (distance, count) = path_counts[&puzzle_state];
+
+            assert_eq!(distance, optimal_solutions.solution_length());
+            assert_eq!(count, optimal_solutions.count());
+
+            let solutions = optimal_solutions.solutions(usize::MAX);
+
+            assert_eq!(count as usize, solutions.len());
+            let direction_indices: Vec<Vec<_>> = solutions
+                .iter()
+                .map(|steps| {
+                    steps
+                        .iter()
+                        .map(|&(step, _)| {
+                            DIRECTIONS.iter().position(|&direction| direction == step)
+                        })
+                        .collect()
+                })
+                .collect();
+
+            // Solutions are unique and sorted.
+            assert!(direction_indices.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(solutions
+                .iter()
+                .all(|steps| is_solution(puzzle_state, steps)));
+        }
+    }
+
+    #[test]
+    fn solutions_of_several_blanks_move_every_blank() {
+        let puzzle_states = [
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::new_with_blanks([
+                [Some(1), Some(2), Some(3)],
+                [Some(4), None, Some(5)],
+                [Some(7), Some(6), None],
+            ])
+            .unwrap(),
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::new_with_blanks([
+                [Some(1), Some(2), Some(3)],
+                [None, Some(4), Some(5)],
+                [Some(7), Some(6), None],
+            ])
+            .unwrap(),
+        ];
+
+        for (puzzle_state, expected_count) in puzzle_states.into_iter().zip([2, 3]) {
+            let optimal_solutions = find_optimal_solutions(
+                puzzle_state,
+                &ManhattanDistance::new(),
+                SearchOptions::new(),
+            )
+            .unwrap();
+            let solutions = optimal_solutions.solutions(usize::MAX);
+
+            assert_eq!(expected_count, optimal_solutions.count());
+            assert_eq!(expected_count as usize, solutions.len());
+            assert!(solutions
+                .iter()
+                .all(|steps| is_solution(puzzle_state, steps)));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+/*!
+* Partial goal of search, which requires only some tiles to be at given cells and doesn't care
+* about the others.
+*/
+
+use std::str::FromStr;
+
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::PuzzleState;
+
+/// Pattern member marking cell with any number.
+const DONT_CARE: &str = "*";
+
+/// Errors that can occur when creating [PartialGoal].
+#[derive(Debug, PartialEq, Eq)]
+pub enum PartialGoalError {
+    /// No brackets around pattern.
+    NoBrackets,
+    /// Not enough cells in pattern.
+    NotEnoughCells,
+    /// Too many cells in pattern.
+    TooManyCells,
+    /// Number parse error.
+    NumberParseError,
+    /// Number is not on board of given size.
+    InvalidNumber(u8),
+    /// Number (`None` for blank) is required at more than one cell.
+    DuplicateNumber(Option<u8>),
+}
+
+/// Goal requiring given numbers (`None` for blank) at given cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialGoal<const PUZZLE_SIZE: usize> {
+    targets: Vec<(Option<u8>, BoardCoordinates<PUZZLE_SIZE>)>,
+}
+
+impl<const PUZZLE_SIZE: usize> PartialGoal<PUZZLE_SIZE> {
+    /// Creates [PartialGoal] requiring `tiles` to be at their positions in solved state.
+    pub fn from_tiles(tiles: &[u8]) -> Result<Self, PartialGoalError> {
+        let solved_state = PuzzleState::<PUZZLE_SIZE>::solved();
+        let mut targets = Vec::with_capacity(tiles.len());
+
+        for &tile in tiles {
+            Self::check_number(&targets, Some(tile))?;
+            targets.push((Some(tile), solved_state.number_coordinates(Some(tile))));
+        }
+
+        Ok(PartialGoal { targets })
+    }
+
+    /// Returns required numbers (`None` for blank) with their cells.
+    pub fn targets(&self) -> &[(Option<u8>, BoardCoordinates<PUZZLE_SIZE>)] {
+        &self.targets
+    }
+
+    /// Checks if all required numbers are at their cells in `puzzle_state`.
+    pub fn is_reached(&self, puzzle_state: &PuzzleState<PUZZLE_SIZE>) -> bool {
+        self.targets
+            .iter()
+            .all(|&(number, coordinates)| puzzle_state.number_at(coordinates) == number)
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> PartialGoal<PUZZLE_SIZE> {
+    fn check_number(
+        targets: &[(Option<u8>, BoardCoordinates<PUZZLE_SIZE>)],
+        number: Option<u8>,
+    ) -> Result<(), PartialGoalError> {
+        if let Some(number_value) = number {
+            if number_value == 0 || number_value as usize >= PUZZLE_SIZE * PUZZLE_SIZE {
+                return Err(PartialGoalError::InvalidNumber(number_value));
+            }
+        }
+
+        if targets
+            .iter()
+            .any(|&(target_number, _)| target_number == number)
+        {
+            Err(PartialGoalError::DuplicateNumber(number))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/**
+* Parses pattern in the same format as [PuzzleState], where `*` marks cell with any number,
+* e.g. `[1, 2, *, *]`.
+*/
+impl<const PUZZLE_SIZE: usize> FromStr for PartialGoal<PUZZLE_SIZE> {
+    type Err = PartialGoalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let pattern_start_index = s.find('[').ok_or(PartialGoalError::NoBrackets)?;
+        let pattern_end_index = s.find(']').ok_or(PartialGoalError::NoBrackets)?;
+
+        let pattern = &s[pattern_start_index + '['.len_utf8()..pattern_end_index];
+        let mut pattern_members = pattern.split(',');
+        let mut targets = vec![];
+
+        for row in 0..PUZZLE_SIZE {
+            for column in 0..PUZZLE_SIZE {
+                let pattern_member = pattern_members
+                    .next()
+                    .ok_or(PartialGoalError::NotEnoughCells)?
+                    .trim();
+
+                if pattern_member == DONT_CARE {
+                    continue;
+                }
+
+                let number = if pattern_member.is_empty() {
+                    None
+                } else {
+                    Some(
+                        pattern_member
+                            .parse::<u8>()
+                            .map_err(|_| PartialGoalError::NumberParseError)?,
+                    )
+                };
+
+                Self::check_number(&targets, number)?;
+                targets.push((number, BoardCoordinates::new(row as u8, column as u8)));
+            }
+        }
+
+        if pattern_members.next().is_some() {
+            Err(PartialGoalError::TooManyCells)
+        } else {
+            Ok(PartialGoal { targets })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn from_tiles_works() {
+        let partial_goal = PartialGoal::<BIGGER_PUZZLE_SIZE>::from_tiles(&[1, 2, 3]).unwrap();
+
+        assert!(partial_goal.is_reached(&PuzzleState::solved()));
+        assert!(partial_goal.is_reached(&"[1, 2, 3, 8, 7, 6, 5, 4, ]".parse().unwrap()));
+        assert!(!partial_goal.is_reached(&"[1, 3, 2, 4, 5, 6, 7, 8, ]".parse().unwrap()));
+
+        assert_eq!(
+            Err(PartialGoalError::InvalidNumber(9)),
+            PartialGoal::<BIGGER_PUZZLE_SIZE>::from_tiles(&[9])
+        );
+        assert_eq!(
+            Err(PartialGoalError::DuplicateNumber(Some(1))),
+            PartialGoal::<BIGGER_PUZZLE_SIZE>::from_tiles(&[1, 1])
+        );
+    }
+
+    #[test]
+    fn parsing_works() {
+        let partial_goal = "[*, 1, , *]".parse::<PartialGoal<PUZZLE_SIZE>>().unwrap();
+
+        assert_eq!(
+            &[
+                (Some(1), BoardCoordinates::new(0, 1)),
+                (None, BoardCoordinates::new(1, 0))
+            ],
+            partial_goal.targets()
+        );
+        assert!(partial_goal.is_reached(&"[3, 1, , 2]".parse().unwrap()));
+        assert!(!partial_goal.is_reached(&"[, 1, 3, 2]".parse().unwrap()));
+
+        assert_eq!(
+            Err(PartialGoalError::NotEnoughCells),
+            "[*, 1, *]".parse::<PartialGoal<PUZZLE_SIZE>>()
+        );
+        assert_eq!(
+            Err(PartialGoalError::TooManyCells),
+            "[*, 1, *, *, *]".parse::<PartialGoal<PUZZLE_SIZE>>()
+        );
+        assert_eq!(
+            Err(PartialGoalError::DuplicateNumber(None)),
+            "[, 1, , *]".parse::<PartialGoal<PUZZLE_SIZE>>()
+        );
+        assert_eq!(
+            Err(PartialGoalError::NumberParseError),
+            "[x, 1, *, *]".parse::<PartialGoal<PUZZLE_SIZE>>()
+        );
+    }
+}
@@ -1,5 +1,7 @@
 //! Coordinates for sliding puzzle board.
 
+use super::direction::Direction;
+
 /// Struct for holding coordinates on puzzle board.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct BoardCoordinates<const PUZZLE_SIZE: usize> {
@@ -29,6 +31,14 @@ impl<const PUZZLE_SIZE: usize> BoardCoordinates<PUZZLE_SIZE> {
         (self.row, self.column)
     }
 
+    /// Returns coordinates reflected about main board diagonal.
+    pub fn transposed(&self) -> Self {
+        BoardCoordinates {
+            row: self.column,
+            column: self.row,
+        }
+    }
+
     /// Calculates distance between two [Coord2D] instances.
     pub fn manhattan_distance(&self, other: &Self) -> u8 {
         self.row.abs_diff(other.row) + self.column.abs_diff(other.column)
@@ -60,6 +70,47 @@ impl<const PUZZLE_SIZE: usize> BoardCoordinates<PUZZLE_SIZE> {
     pub fn at_bottom_edge(&self) -> bool {
         self.row == (PUZZLE_SIZE - 1) as u8
     }
+
+    /// Calculates distance between two coordinates on board wrapping around its edges.
+    pub fn toroidal_distance(&self, other: &Self) -> u8 {
+        let row_distance = self.row.abs_diff(other.row);
+        let column_distance = self.column.abs_diff(other.column);
+
+        row_distance.min(PUZZLE_SIZE as u8 - row_distance)
+            + column_distance.min(PUZZLE_SIZE as u8 - column_distance)
+    }
+
+    /// Returns coordinates of neighbouring cell in given `direction`, wrapping around board edges.
+    pub fn wrapping_neighbour(&self, direction: Direction) -> Self {
+        let (row_difference, column_difference) = direction.as_coordinates();
+        let size = PUZZLE_SIZE as isize;
+
+        BoardCoordinates {
+            row: (self.row as isize + row_difference).rem_euclid(size) as u8,
+            column: (self.column as isize + column_difference).rem_euclid(size) as u8,
+        }
+    }
+
+    /// Returns coordinates of neighbouring cell in given `direction` (`None` at board edge).
+    pub fn neighbour(&self, direction: Direction) -> Option<Self> {
+        let at_edge = match direction {
+            Direction::Up => self.at_upper_edge(),
+            Direction::Down => self.at_bottom_edge(),
+            Direction::Left => self.at_left_edge(),
+            Direction::Right => self.at_right_edge(),
+        };
+
+        if at_edge {
+            return None;
+        }
+
+        let (row_difference, column_difference) = direction.as_coordinates();
+
+        Some(BoardCoordinates {
+            row: (self.row as isize + row_difference) as u8,
+            column: (self.column as isize + column_difference) as u8,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +141,56 @@ mod tests {
         assert_eq!(manhattan_distance, reverse_manhattan_distance);
     }
 
+    #[test]
+    fn neighbour_coordinates() {
+        let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(0, 3);
+
+        assert_eq!(None, coordinates.neighbour(Direction::Up));
+        assert_eq!(None, coordinates.neighbour(Direction::Right));
+        assert_eq!(
+            Some(BoardCoordinates::new(1, 3)),
+            coordinates.neighbour(Direction::Down)
+        );
+        assert_eq!(
+            Some(BoardCoordinates::new(0, 2)),
+            coordinates.neighbour(Direction::Left)
+        );
+    }
+
+    #[test]
+    fn wrapping_coordinates() {
+        let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(0, 3);
+
+        assert_eq!(
+            BoardCoordinates::new(3, 3),
+            coordinates.wrapping_neighbour(Direction::Up)
+        );
+        assert_eq!(
+            BoardCoordinates::new(0, 0),
+            coordinates.wrapping_neighbour(Direction::Right)
+        );
+        assert_eq!(
+            BoardCoordinates::new(1, 3),
+            coordinates.wrapping_neighbour(Direction::Down)
+        );
+        assert_eq!(
+            2,
+            coordinates.toroidal_distance(&BoardCoordinates::new(3, 0))
+        );
+        assert_eq!(
+            4,
+            coordinates.toroidal_distance(&BoardCoordinates::new(2, 1))
+        );
+    }
+
+    #[test]
+    fn transposed_coordinates() {
+        let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(1, 3);
+
+        assert_eq!(BoardCoordinates::new(3, 1), coordinates.transposed());
+        assert_eq!(coordinates, coordinates.transposed().transposed());
+    }
+
     #[test]
     fn correct_blank_manhattan_distance() {
         let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(1, 2);
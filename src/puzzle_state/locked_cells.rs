@@ -0,0 +1,97 @@
+//! [LockedCells] which blank can't enter, so tiles in them are never moved.
+
+use super::coordinates::BoardCoordinates;
+use super::PuzzleState;
+
+/// Set of board cells which blank can't move into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockedCells<const PUZZLE_SIZE: usize> {
+    /// Bit for every cell index (row by row).
+    mask: u16,
+}
+
+impl<const PUZZLE_SIZE: usize> LockedCells<PUZZLE_SIZE> {
+    /// Creates [LockedCells] without any locked cell.
+    pub fn new() -> Self {
+        LockedCells::default()
+    }
+
+    /// Locks cell at `coordinates`.
+    pub fn with_cell(self, coordinates: BoardCoordinates<PUZZLE_SIZE>) -> Self {
+        LockedCells {
+            mask: self.mask | (1 << Self::cell_index(coordinates)),
+        }
+    }
+
+    /**
+     * Creates [LockedCells] with cells where `tiles` are in `puzzle_state`.
+     *
+     * # Panics
+     *
+     * If some tile is not on board.
+     */
+    pub fn of_tiles(puzzle_state: &PuzzleState<PUZZLE_SIZE>, tiles: &[u8]) -> Self {
+        tiles
+            .iter()
+            .fold(LockedCells::new(), |locked_cells, &tile| {
+                locked_cells.with_cell(puzzle_state.number_coordinates(Some(tile)))
+            })
+    }
+
+    /// Checks if cell at `coordinates` is locked.
+    pub fn contains(&self, coordinates: BoardCoordinates<PUZZLE_SIZE>) -> bool {
+        self.contains_index(Self::cell_index(coordinates))
+    }
+
+    /// Checks if no cell is locked.
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> LockedCells<PUZZLE_SIZE> {
+    pub(super) fn contains_index(&self, cell_index: usize) -> bool {
+        self.mask & (1 << cell_index) != 0
+    }
+
+    fn cell_index(coordinates: BoardCoordinates<PUZZLE_SIZE>) -> usize {
+        let (row, column) = coordinates.as_tuple();
+
+        row as usize * PUZZLE_SIZE + column as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Direction;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn locked_cells_are_not_entered() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+        let locked_cells = LockedCells::of_tiles(&puzzle_state, &[2, 5]);
+
+        assert!(locked_cells.contains(BoardCoordinates::new(0, 1)));
+        assert!(locked_cells.contains(BoardCoordinates::new(1, 2)));
+        assert!(!locked_cells.contains(BoardCoordinates::new(1, 1)));
+
+        let neighbours = puzzle_state.neighbours().with_locked_cells(locked_cells);
+
+        assert_eq!(2, neighbours.len());
+        assert_eq!(
+            vec![Direction::Down, Direction::Left],
+            neighbours
+                .map(|neighbour| neighbour.into_direction_and_puzzle_state().0)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -3,41 +3,114 @@
 pub mod coordinates;
 pub mod direction;
 pub mod errors;
+pub mod locked_cells;
+pub mod obstacles;
 pub mod parity_check_permutation;
 pub mod puzzle_move;
+mod ranking;
+pub mod topology;
 
 use std::collections::HashSet;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 use coordinates::BoardCoordinates;
 use direction::Direction;
 use errors::{PuzzleStateCreationError, PuzzleStateParseError};
+use obstacles::Obstacles;
 use parity_check_permutation::ParityCheckPermutation;
-use puzzle_move::Move;
+use puzzle_move::Neighbours;
+use topology::Topology;
 
 use crate::heuristics::Heuristic;
 
 const BLANK_NUMBER: u64 = 0b1111;
 const MAX_NUMBER_WIDTH: usize = 4;
 
-/// Stores puzzle state for sliding puzzle game of `PUZZLE_SIZE` size.
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/**
+ * Stores puzzle state for sliding puzzle game of `PUZZLE_SIZE` size.
+ * Board can have several blanks, then numbers go from 1 to number of non-blank cells.
+ * Moves depend on board [Topology], which is flat unless set by [PuzzleState::with_topology],
+ * and on board [Obstacles] set by [PuzzleState::with_obstacles].
+ */
+#[derive(Debug, Clone, Copy)]
 pub struct PuzzleState<const PUZZLE_SIZE: usize> {
     numbers: u64,
+    /// Index of (first) blank cell row by row, it is determined by `numbers`.
+    blank_index: u8,
+    topology: Topology,
+    obstacles: Obstacles<PUZZLE_SIZE>,
 }
 
 // API impl block
 impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
-    /// Creates new instance of [PuzzleState].
+    /// Creates new instance of [PuzzleState] with single blank.
     pub fn new(
         numbers: [[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE],
     ) -> Result<Self, PuzzleStateCreationError> {
-        Self::check_numbers(&numbers)?;
+        Self::check_numbers(&numbers, false)?;
 
-        Ok(PuzzleState {
-            numbers: Self::numbers_from_readable(&numbers),
-        })
+        Ok(Self::from_numbers(Self::numbers_from_readable(&numbers)))
+    }
+
+    /**
+     * Creates new instance of [PuzzleState] with one or more blanks. Numbers have to go from 1 to
+     * number of non-blank cells.
+     */
+    pub fn new_with_blanks(
+        numbers: [[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE],
+    ) -> Result<Self, PuzzleStateCreationError> {
+        Self::check_numbers(&numbers, true)?;
+
+        Ok(Self::from_numbers(Self::numbers_from_readable(&numbers)))
+    }
+
+    /// Sets board `topology`, which decides where blank can move.
+    pub fn with_topology(self, topology: Topology) -> Self {
+        PuzzleState { topology, ..self }
+    }
+
+    /// Accessor for `topology` field.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /**
+     * Sets board `obstacles`, cells which no tile can enter. Numbers in them never move, so they
+     * have to be the numbers from solved state for state to be solvable.
+     */
+    pub fn with_obstacles(self, obstacles: Obstacles<PUZZLE_SIZE>) -> Self {
+        PuzzleState { obstacles, ..self }
+    }
+
+    /// Accessor for `obstacles` field.
+    pub fn obstacles(&self) -> Obstacles<PUZZLE_SIZE> {
+        self.obstacles
+    }
+
+    /// Creates solved [PuzzleState] (numbers in order with blank in bottom right corner).
+    pub fn solved() -> Self {
+        Self::solved_with_blanks(1)
+    }
+
+    /**
+     * Creates solved [PuzzleState] with `blanks_count` blanks (numbers in order followed by
+     * blanks).
+     *
+     * # Panics
+     *
+     * If `blanks_count` is zero or greater than number of board cells.
+     */
+    pub fn solved_with_blanks(blanks_count: usize) -> Self {
+        let cells_count = PUZZLE_SIZE * PUZZLE_SIZE;
+
+        assert!(
+            (1..=cells_count).contains(&blanks_count),
+            "Invalid number of blanks: {blanks_count} for puzzle size: {PUZZLE_SIZE}"
+        );
+
+        Self::from_numbers(Self::solved_numbers(blanks_count))
     }
 
     /// Returns currently contained numbers in readable form.
@@ -47,99 +120,311 @@ impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
 
     /// Checks if state is a valid solution in sliding puzzle game.
     pub fn is_solved(&self) -> bool {
+        let blanks_count = self.blanks_count();
+
+        if blanks_count > 1 {
+            return self.numbers == Self::solved_numbers(blanks_count);
+        }
+
         let blank_manhattan_distance = self.blank_position().blank_manhattan_distance();
 
         (blank_manhattan_distance == 0) && self.is_solved_permutation()
     }
 
-    /// Checks if goal state is achievable from this state.
+    /// Returns number of blank cells.
+    pub fn blanks_count(&self) -> usize {
+        self.blank_nibbles().count_ones() as usize
+    }
+
+    /**
+     * Checks if goal state is achievable from this state.
+     * States with several blanks are always solvable, because numbers can pass each other.
+     * On toroidal board of odd size, blank going around row performs odd permutation, so all
+     * states are solvable. Other boards are bipartite and usual parity check applies.
+     */
     pub fn is_solvable(&self) -> bool {
+        if self.blanks_count() > 1 || (self.topology.wraps::<PUZZLE_SIZE>() && PUZZLE_SIZE % 2 == 1)
+        {
+            return true;
+        }
+
         let parity_check_permutation =
             ParityCheckPermutation::from_numbers(&self.readable_numbers());
         let blank_manhattan_distance = self.blank_position().blank_manhattan_distance();
-        let is_blank_manhattan_distance_even = (blank_manhattan_distance % 2) == 0;
+        let is_blank_manhattan_distance_even = blank_manhattan_distance.is_multiple_of(2);
 
         (parity_check_permutation.is_even() && is_blank_manhattan_distance_even)
             || (!parity_check_permutation.is_even() && !is_blank_manhattan_distance_even)
     }
 
-    /// Creates state obtained by moving blank in given `direction`.
+    /**
+     * Creates state obtained by moving blank in given `direction` by swapping blank with
+     * neighbouring number in packed representation. With several blanks it moves blank at
+     * [PuzzleState::blank_position], other blanks can be moved by
+     * [PuzzleState::create_blank_move_state].
+     */
     pub fn create_neighbour_move_state(&self, direction: Direction) -> PuzzleState<PUZZLE_SIZE> {
-        let (diff_row, diff_column) = direction.as_coordinates();
-        let (blank_row, blank_column) = self.blank_position().as_tuple();
-
-        // Values for blanks can't be so big, not to fit isize.
-        // blank + diff values can't be negative (checked before this function call).
-        let swap_row = ((blank_row as isize) + diff_row) as usize;
-        let swap_column = ((blank_column as isize) + diff_column) as usize;
+        self.create_blank_index_move_state(self.blank_index, direction)
+    }
 
-        let mut new_numbers = self.readable_numbers();
-        let swap_number = new_numbers[swap_row][swap_column];
+    /**
+     * Creates state obtained by moving blank at `blank` coordinates in given `direction`.
+     * Returns `None` if there is no blank at `blank`, if it can't move in `direction` (see
+     * [PuzzleState::can_move]) or if it would move into other blank.
+     */
+    pub fn create_blank_move_state(
+        &self,
+        blank: BoardCoordinates<PUZZLE_SIZE>,
+        direction: Direction,
+    ) -> Option<PuzzleState<PUZZLE_SIZE>> {
+        let blank_index = Self::cell_index(blank);
+
+        if self.number_at(blank).is_some() || !self.can_move_blank_index(blank_index, direction) {
+            return None;
+        }
 
-        new_numbers[swap_row][swap_column] = None;
-        new_numbers[blank_row as usize][blank_column as usize] = swap_number;
+        let swap_index = self.blank_neighbour_index(blank_index, direction);
 
-        PuzzleState {
-            numbers: Self::numbers_from_readable(&new_numbers),
+        if self.blank_cells_mask() & (1 << swap_index) != 0 {
+            return None;
         }
+
+        Some(self.create_blank_index_move_state(blank_index, direction))
     }
 
-    /// Creates states obtainable from current one by performing one move.
-    pub fn neighbours(&self) -> Vec<Move<PUZZLE_SIZE>> {
-        let mut moves = vec![];
+    /**
+     * Creates state obtained by moving `tile` into neighbouring blank, so that blank moves in
+     * given `direction` (as by steps of [crate::Solution] together with their moved tiles).
+     * Returns `None` if `tile` isn't on board or such move isn't possible.
+     */
+    pub fn create_tile_move_state(
+        &self,
+        tile: u8,
+        direction: Direction,
+    ) -> Option<PuzzleState<PUZZLE_SIZE>> {
+        let tile_index = (0..PUZZLE_SIZE * PUZZLE_SIZE)
+            .find(|&cell_index| self.number_at(Self::cell_coordinates(cell_index)) == Some(tile))?;
+        let blank = self
+            .topology
+            .neighbour(Self::cell_coordinates(tile_index), direction.opposite())?;
+
+        self.create_blank_move_state(blank, direction)
+    }
 
-        if !self.blank_position().at_upper_edge() {
-            moves.push(Move::new(
-                Direction::Up,
-                self.create_neighbour_move_state(Direction::Up),
-            ));
-        }
+    /**
+     * Checks if blank can be moved in given `direction` (it is not at board edge or board wraps,
+     * and it doesn't enter obstacle). With several blanks it checks blank at
+     * [PuzzleState::blank_position].
+     */
+    pub fn can_move(&self, direction: Direction) -> bool {
+        self.can_move_blank_index(self.blank_index, direction)
+    }
 
-        if !self.blank_position().at_bottom_edge() {
-            moves.push(Move::new(
-                Direction::Down,
-                self.create_neighbour_move_state(Direction::Down),
-            ));
-        }
+    /// Returns iterator over states obtainable from current one by performing one move.
+    pub fn neighbours(&self) -> Neighbours<PUZZLE_SIZE> {
+        Neighbours::new(*self)
+    }
 
-        if !self.blank_position().at_left_edge() {
-            moves.push(Move::new(
-                Direction::Left,
-                self.create_neighbour_move_state(Direction::Left),
-            ));
+    /// Calculates `heuristic` value on state.
+    pub fn calculate_heuristic(&self, heuristic: &dyn Heuristic<PUZZLE_SIZE>) -> u8 {
+        heuristic.calculate(&self.readable_numbers())
+    }
+
+    /**
+     * Calculates `heuristic` value on state obtained by moving blank from `previous_blank` to
+     * `blank` coordinates, knowing that `previous_value` was heuristic value before move.
+     * It uses [Heuristic::calculate_delta] if heuristic supports it.
+     */
+    pub fn calculate_heuristic_after_move(
+        &self,
+        heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+        previous_value: u8,
+        blank: BoardCoordinates<PUZZLE_SIZE>,
+        previous_blank: BoardCoordinates<PUZZLE_SIZE>,
+    ) -> u8 {
+        let moved_number = self
+            .number_at(previous_blank)
+            .expect("Number has to be moved to previous blank coordinates.");
+
+        heuristic
+            .calculate_delta(previous_value, moved_number, blank, previous_blank, self)
+            .unwrap_or_else(|| self.calculate_heuristic(heuristic))
+    }
+
+    /// Returns number at given `coordinates` (`None` for blank).
+    pub fn number_at(&self, coordinates: BoardCoordinates<PUZZLE_SIZE>) -> Option<u8> {
+        let (row, column) = coordinates.as_tuple();
+        let number_index = (row as usize) * PUZZLE_SIZE + (column as usize);
+        let internal_number = (self.numbers >> (MAX_NUMBER_WIDTH * number_index)) & 0b1111;
+
+        if internal_number < BLANK_NUMBER {
+            Some((internal_number + 1) as u8)
+        } else {
+            None
         }
+    }
 
-        if !self.blank_position().at_right_edge() {
-            moves.push(Move::new(
-                Direction::Right,
-                self.create_neighbour_move_state(Direction::Right),
-            ));
+    /**
+     * Returns coordinates of `number` (`None` for blank).
+     *
+     * # Panics
+     *
+     * If `number` is not on board.
+     */
+    pub fn number_coordinates(&self, number: Option<u8>) -> BoardCoordinates<PUZZLE_SIZE> {
+        let internal_number = number.map_or(BLANK_NUMBER, |number_value| (number_value - 1) as u64);
+
+        for number_index in 0..(PUZZLE_SIZE * PUZZLE_SIZE) {
+            if (self.numbers >> (MAX_NUMBER_WIDTH * number_index)) & 0b1111 == internal_number {
+                return BoardCoordinates::new(
+                    (number_index / PUZZLE_SIZE) as u8,
+                    (number_index % PUZZLE_SIZE) as u8,
+                );
+            }
         }
 
-        moves
+        panic!("Number {number:?} not found on board");
     }
 
-    /// Calculates `heuristic` value on state.
-    pub fn calculate_heuristic(&self, heuristic: &dyn Heuristic<PUZZLE_SIZE>) -> u8 {
-        heuristic.calculate(&self.readable_numbers())
+    /// Returns blank position (of the first blank row by row if there are several blanks).
+    pub fn blank_position(&self) -> BoardCoordinates<PUZZLE_SIZE> {
+        Self::cell_coordinates(self.blank_index as usize)
     }
 }
 
 // Private impl block
 impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
-    /// Returns calculated blank position
-    fn blank_position(&self) -> BoardCoordinates<PUZZLE_SIZE> {
-        let readable_numbers = self.readable_numbers();
+    /// Returns numbers in internal form, 4 bits per board cell (row by row from lowest bits).
+    pub(crate) fn packed_numbers(&self) -> u64 {
+        self.numbers
+    }
 
-        for (row, numbers_row) in readable_numbers.iter().enumerate() {
-            for (column, number) in numbers_row.iter().enumerate() {
-                if number.is_none() {
-                    return BoardCoordinates::new(row as u8, column as u8);
-                }
+    /// Returns index of cell which blank at `blank_index` enters when moving in `direction`.
+    pub(crate) fn blank_neighbour_index(&self, blank_index: u8, direction: Direction) -> usize {
+        if self.topology.wraps::<PUZZLE_SIZE>() {
+            let (row, column) = Self::cell_coordinates(blank_index as usize)
+                .wrapping_neighbour(direction)
+                .as_tuple();
+
+            return row as usize * PUZZLE_SIZE + column as usize;
+        }
+
+        let (diff_row, diff_column) = direction.as_coordinates();
+
+        // Blank index can't be so big, not to fit isize.
+        // Swap index can't be negative or out of board (checked before this function call).
+        (blank_index as isize + diff_row * PUZZLE_SIZE as isize + diff_column) as usize
+    }
+
+    /// Checks if blank at `blank_index` can move in `direction`, see [PuzzleState::can_move].
+    pub(crate) fn can_move_blank_index(&self, blank_index: u8, direction: Direction) -> bool {
+        if !self.topology.wraps::<PUZZLE_SIZE>() {
+            let blank_position = Self::cell_coordinates(blank_index as usize);
+            let at_edge = match direction {
+                Direction::Up => blank_position.at_upper_edge(),
+                Direction::Down => blank_position.at_bottom_edge(),
+                Direction::Left => blank_position.at_left_edge(),
+                Direction::Right => blank_position.at_right_edge(),
+            };
+
+            if at_edge {
+                return false;
             }
         }
 
-        unreachable!("Blank has to be found in numbers");
+        self.obstacles.is_empty()
+            || !self
+                .obstacles
+                .contains_index(self.blank_neighbour_index(blank_index, direction))
+    }
+
+    /**
+     * Creates state obtained by moving blank at `blank_index` in given `direction` (move has to
+     * be possible).
+     */
+    pub(crate) fn create_blank_index_move_state(
+        &self,
+        blank_index: u8,
+        direction: Direction,
+    ) -> Self {
+        let swap_index = self.blank_neighbour_index(blank_index, direction);
+        let blank_shift = MAX_NUMBER_WIDTH * blank_index as usize;
+        let swap_shift = MAX_NUMBER_WIDTH * swap_index;
+        let swap_number = (self.numbers >> swap_shift) & BLANK_NUMBER;
+
+        // Blank number has all bits set, so it can be added with bitwise or.
+        let numbers = (self.numbers | (BLANK_NUMBER << swap_shift))
+            & !((BLANK_NUMBER ^ swap_number) << blank_shift);
+
+        PuzzleState {
+            numbers,
+            blank_index: Self::first_blank_index(numbers),
+            ..*self
+        }
+    }
+
+    /// Returns coordinates of cell with `cell_index` (row by row).
+    pub(crate) fn cell_coordinates(cell_index: usize) -> BoardCoordinates<PUZZLE_SIZE> {
+        BoardCoordinates::new(
+            (cell_index / PUZZLE_SIZE) as u8,
+            (cell_index % PUZZLE_SIZE) as u8,
+        )
+    }
+
+    /// Returns index of cell with given `coordinates` (row by row).
+    fn cell_index(coordinates: BoardCoordinates<PUZZLE_SIZE>) -> u8 {
+        let (row, column) = coordinates.as_tuple();
+
+        row * PUZZLE_SIZE as u8 + column
+    }
+
+    /// Returns mask with bit set for every blank cell index.
+    pub(crate) fn blank_cells_mask(&self) -> u16 {
+        let blank_nibbles = self.blank_nibbles();
+
+        (0..PUZZLE_SIZE * PUZZLE_SIZE).fold(0, |mask, cell_index| {
+            mask | ((((blank_nibbles >> (MAX_NUMBER_WIDTH * cell_index)) & 1) as u16) << cell_index)
+        })
+    }
+
+    /// Returns numbers with lowest bit of nibble set only for blank cells.
+    fn blank_nibbles(&self) -> u64 {
+        Self::numbers_blank_nibbles(self.numbers)
+    }
+
+    /// Returns `numbers` in internal form with lowest bit of nibble set only for blank cells.
+    fn numbers_blank_nibbles(numbers: u64) -> u64 {
+        numbers & (numbers >> 1) & (numbers >> 2) & (numbers >> 3) & 0x1111_1111_1111_1111
+    }
+
+    /// Returns index of first blank cell in `numbers` in internal form.
+    fn first_blank_index(numbers: u64) -> u8 {
+        (Self::numbers_blank_nibbles(numbers).trailing_zeros() as usize / MAX_NUMBER_WIDTH) as u8
+    }
+
+    /// Returns solved numbers in internal form with `blanks_count` blanks at the end.
+    fn solved_numbers(blanks_count: usize) -> u64 {
+        let cells_count = PUZZLE_SIZE * PUZZLE_SIZE;
+
+        (0..cells_count).fold(0, |numbers, cell_index| {
+            let internal_number = if cell_index < cells_count - blanks_count {
+                cell_index as u64
+            } else {
+                BLANK_NUMBER
+            };
+
+            numbers | (internal_number << (MAX_NUMBER_WIDTH * cell_index))
+        })
+    }
+
+    /// Creates state from numbers in internal form, finding (first) blank index.
+    fn from_numbers(numbers: u64) -> Self {
+        PuzzleState {
+            numbers,
+            blank_index: Self::first_blank_index(numbers),
+            topology: Topology::Flat,
+            obstacles: Obstacles::default(),
+        }
     }
 
     /// Transforms numbers from internal form to readable form.
@@ -185,22 +470,28 @@ impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
     /// Checks if `numbers` are correct for [PuzzleState]
     fn check_numbers(
         numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE],
+        several_blanks_allowed: bool,
     ) -> Result<(), PuzzleStateCreationError> {
-        let max_number_value = ((PUZZLE_SIZE * PUZZLE_SIZE) as u8) - 1;
-        let mut permutation_numbers: HashSet<_> = (1..=max_number_value).into_iter().collect();
-        let mut blank_found = false;
+        let blanks_count = numbers
+            .iter()
+            .flatten()
+            .filter(|number| number.is_none())
+            .count();
 
-        for number_row in numbers {
-            for number in number_row {
-                if let Some(number_value) = number {
-                    if !permutation_numbers.remove(number_value) {
-                        return Err(PuzzleStateCreationError::NotPermutation);
-                    }
-                } else if !blank_found {
-                    blank_found = true;
-                } else {
-                    return Err(PuzzleStateCreationError::TwoBlanks);
-                }
+        if blanks_count == 0 {
+            return Err(PuzzleStateCreationError::NotPermutation);
+        }
+
+        if blanks_count > 1 && !several_blanks_allowed {
+            return Err(PuzzleStateCreationError::TwoBlanks);
+        }
+
+        let max_number_value = (PUZZLE_SIZE * PUZZLE_SIZE - blanks_count) as u8;
+        let mut permutation_numbers: HashSet<_> = (1..=max_number_value).collect();
+
+        for number_value in numbers.iter().flatten().flatten() {
+            if !permutation_numbers.remove(number_value) {
+                return Err(PuzzleStateCreationError::NotPermutation);
             }
         }
 
@@ -208,7 +499,6 @@ impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
             return Err(PuzzleStateCreationError::NotPermutation);
         }
 
-        // blank has to be found at this stage
         Ok(())
     }
 
@@ -234,6 +524,23 @@ impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
     }
 }
 
+impl<const PUZZLE_SIZE: usize> PartialEq for PuzzleState<PUZZLE_SIZE> {
+    fn eq(&self, other: &Self) -> bool {
+        self.numbers == other.numbers
+            && self.topology == other.topology
+            && self.obstacles == other.obstacles
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Eq for PuzzleState<PUZZLE_SIZE> {}
+
+impl<const PUZZLE_SIZE: usize> Hash for PuzzleState<PUZZLE_SIZE> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.numbers.hash(state);
+    }
+}
+
+/// Parses numbers separated by commas in brackets, blanks are empty, e.g. `[1, 2, 3, ]`.
 impl<const PUZZLE_SIZE: usize> FromStr for PuzzleState<PUZZLE_SIZE> {
     type Err = PuzzleStateParseError;
 
@@ -257,7 +564,7 @@ impl<const PUZZLE_SIZE: usize> FromStr for PuzzleState<PUZZLE_SIZE> {
                     .ok_or(PuzzleStateParseError::NotEnoughNumbers)?
                     .trim();
 
-                if permutation_member != "" {
+                if !permutation_member.is_empty() {
                     let number_value = permutation_member
                         .parse::<u8>()
                         .map_err(|_| PuzzleStateParseError::NumberParseError)?;
@@ -270,7 +577,7 @@ impl<const PUZZLE_SIZE: usize> FromStr for PuzzleState<PUZZLE_SIZE> {
         if permutation_members.next().is_some() {
             Err(PuzzleStateParseError::TooManyNumbers)
         } else {
-            Ok(PuzzleState::new(numbers)?)
+            Ok(PuzzleState::new_with_blanks(numbers)?)
         }
     }
 }
@@ -312,7 +619,7 @@ mod tests {
 
         assert_eq!(readable_numbers, readable_from_internal_numbers);
         assert_eq!(
-            0b00000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_1111_0010_0001_0000,
+            0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_1111_0010_0001_0000,
             internal_numbers
         );
     }
@@ -337,6 +644,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn several_blanks_puzzle_state() {
+        let puzzle_state =
+            PuzzleState::<PUZZLE_SIZE>::new_with_blanks([[Some(1), None], [None, Some(2)]])
+                .unwrap();
+
+        assert_eq!(2, puzzle_state.blanks_count());
+        assert!(puzzle_state.is_solvable());
+        assert!(!puzzle_state.is_solved());
+
+        let puzzle_state =
+            PuzzleState::<PUZZLE_SIZE>::new_with_blanks([[Some(1), Some(3)], [None, None]]);
+
+        assert!(matches!(
+            puzzle_state,
+            Err(PuzzleStateCreationError::NotPermutation)
+        ));
+    }
+
+    #[test]
+    fn solved_with_blanks_works() {
+        let solved_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::solved_with_blanks(2);
+
+        assert!(solved_state.is_solved());
+        assert_eq!(2, solved_state.blanks_count());
+        assert_eq!("[1, 2, 3, 4, 5, 6, 7, , ]", solved_state.to_string());
+        assert_eq!(
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved(),
+            PuzzleState::solved_with_blanks(1)
+        );
+    }
+
     #[test]
     fn valid_puzzle_state_created() {
         let puzzle_state = PuzzleState::<PUZZLE_SIZE>::new([[Some(2), Some(1)], [None, Some(3)]]);
@@ -357,6 +696,29 @@ mod tests {
         assert_eq!(BoardCoordinates::new(0, 1), puzzle_state.blank_position());
     }
 
+    #[test]
+    fn number_lookups_work() {
+        let puzzle_state =
+            PuzzleState::<PUZZLE_SIZE>::new([[Some(2), Some(1)], [None, Some(3)]]).unwrap();
+
+        assert_eq!(Some(2), puzzle_state.number_at(BoardCoordinates::new(0, 0)));
+        assert_eq!(Some(3), puzzle_state.number_at(BoardCoordinates::new(1, 1)));
+        assert_eq!(None, puzzle_state.number_at(BoardCoordinates::new(1, 0)));
+
+        assert_eq!(
+            BoardCoordinates::new(0, 1),
+            puzzle_state.number_coordinates(Some(1))
+        );
+        assert_eq!(
+            BoardCoordinates::new(1, 1),
+            puzzle_state.number_coordinates(Some(3))
+        );
+        assert_eq!(
+            BoardCoordinates::new(1, 0),
+            puzzle_state.number_coordinates(None)
+        );
+    }
+
     #[test]
     fn not_solved_state() {
         let puzzle_state =
@@ -378,6 +740,16 @@ mod tests {
         assert!(puzzle_state.is_solved());
     }
 
+    #[test]
+    fn solved_constructor() {
+        assert_eq!(
+            PuzzleState::<PUZZLE_SIZE>::new([[Some(1), Some(2)], [Some(3), None]]).unwrap(),
+            PuzzleState::solved()
+        );
+
+        assert!(PuzzleState::<BIGGEST_PUZZLE_SIZE>::solved().is_solved());
+    }
+
     #[test]
     fn solvable_state() {
         let puzzle_state =
@@ -429,9 +801,7 @@ mod tests {
             [Some(3), Some(2)],
         ]);
 
-        let expected_obtained_state = PuzzleState {
-            numbers: expected_numbers,
-        };
+        let expected_obtained_state = PuzzleState::from_numbers(expected_numbers);
 
         let obtained_state = puzzle_state.create_neighbour_move_state(Direction::Up);
 
@@ -448,9 +818,7 @@ mod tests {
             [None, Some(2)],
         ]);
 
-        let expected_obtained_state = PuzzleState {
-            numbers: expected_numbers,
-        };
+        let expected_obtained_state = PuzzleState::from_numbers(expected_numbers);
 
         let obtained_state = puzzle_state.create_neighbour_move_state(Direction::Down);
 
@@ -467,9 +835,7 @@ mod tests {
             [Some(3), Some(2)],
         ]);
 
-        let expected_obtained_state = PuzzleState {
-            numbers: expected_numbers,
-        };
+        let expected_obtained_state = PuzzleState::from_numbers(expected_numbers);
 
         let obtained_state = puzzle_state.create_neighbour_move_state(Direction::Left);
 
@@ -486,15 +852,28 @@ mod tests {
             [Some(3), Some(2)],
         ]);
 
-        let expected_obtained_state = PuzzleState {
-            numbers: expected_numbers,
-        };
+        let expected_obtained_state = PuzzleState::from_numbers(expected_numbers);
 
         let obtained_state = puzzle_state.create_neighbour_move_state(Direction::Right);
 
         assert_eq!(expected_obtained_state, obtained_state);
     }
 
+    #[test]
+    fn can_move_works() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [None, Some(1), Some(2)],
+            [Some(3), Some(4), Some(5)],
+            [Some(6), Some(7), Some(8)],
+        ])
+        .unwrap();
+
+        assert!(!puzzle_state.can_move(Direction::Up));
+        assert!(puzzle_state.can_move(Direction::Down));
+        assert!(!puzzle_state.can_move(Direction::Left));
+        assert!(puzzle_state.can_move(Direction::Right));
+    }
+
     #[test]
     fn two_neighbours() {
         let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
@@ -537,6 +916,188 @@ mod tests {
         assert_eq!(4, neighbours.len());
     }
 
+    #[test]
+    fn moves_keep_blank_index_and_numbers_consistent() {
+        let mut puzzle_state = PuzzleState::<BIGGEST_PUZZLE_SIZE>::solved();
+
+        for direction in [
+            Direction::Up,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+            Direction::Up,
+            Direction::Right,
+        ] {
+            let previous_state = puzzle_state;
+
+            puzzle_state = puzzle_state.create_neighbour_move_state(direction);
+
+            assert_eq!(
+                PuzzleState::new(puzzle_state.readable_numbers()).unwrap(),
+                puzzle_state
+            );
+            assert_eq!(
+                previous_state,
+                puzzle_state.create_neighbour_move_state(direction.opposite())
+            );
+        }
+
+        let solved_neighbours_directions: Vec<_> = PuzzleState::<BIGGEST_PUZZLE_SIZE>::solved()
+            .neighbours()
+            .map(|neighbour| neighbour.into_direction_and_puzzle_state().0)
+            .collect();
+
+        assert_eq!(
+            vec![Direction::Up, Direction::Left],
+            solved_neighbours_directions
+        );
+    }
+
+    #[test]
+    fn several_blanks_neighbours() {
+        let puzzle_state = "[1, , 2, 3, , 5, 6, 7, 4]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let neighbours = puzzle_state.neighbours();
+
+        assert_eq!(5, neighbours.len());
+
+        // Blanks don't move into each other, upper blank moves first.
+        let moves: Vec<_> = neighbours.collect();
+
+        assert_eq!(
+            vec![
+                Direction::Left,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right
+            ],
+            moves
+                .iter()
+                .map(|neighbour| neighbour.into_direction_and_puzzle_state().0)
+                .collect::<Vec<_>>()
+        );
+
+        let (_, neighbour_state) = moves[3].into_direction_and_puzzle_state();
+
+        assert_eq!("[1, , 2, , 3, 5, 6, 7, 4]", neighbour_state.to_string());
+        assert_eq!(BoardCoordinates::new(1, 0), moves[3].blank_position());
+        assert_eq!(
+            BoardCoordinates::new(0, 1),
+            neighbour_state.blank_position()
+        );
+
+        for neighbour in moves {
+            let (direction, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+            assert_eq!(2, neighbour_state.blanks_count());
+            assert_eq!(
+                Some(puzzle_state),
+                neighbour_state
+                    .create_blank_move_state(neighbour.blank_position(), direction.opposite())
+            );
+        }
+    }
+
+    #[test]
+    fn blank_and_tile_moves_work() {
+        let puzzle_state = "[1, , 2, 3, , 5, 6, 7, 4]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+        let moved_state = "[1, , 2, 3, 7, 5, 6, , 4]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+
+        assert_eq!(
+            Some(moved_state),
+            puzzle_state.create_blank_move_state(BoardCoordinates::new(1, 1), Direction::Down)
+        );
+        assert_eq!(
+            Some(moved_state),
+            puzzle_state.create_tile_move_state(7, Direction::Down)
+        );
+        assert_eq!(
+            None,
+            puzzle_state.create_blank_move_state(BoardCoordinates::new(1, 1), Direction::Up)
+        );
+        assert_eq!(
+            None,
+            puzzle_state.create_blank_move_state(BoardCoordinates::new(0, 0), Direction::Right)
+        );
+        assert_eq!(
+            None,
+            puzzle_state.create_tile_move_state(4, Direction::Down)
+        );
+        assert_eq!(
+            None,
+            puzzle_state.create_tile_move_state(8, Direction::Down)
+        );
+
+        // Equal states move the same blank.
+        let same_state = moved_state
+            .create_blank_move_state(BoardCoordinates::new(2, 1), Direction::Up)
+            .unwrap()
+            .create_blank_move_state(BoardCoordinates::new(1, 1), Direction::Down)
+            .unwrap();
+
+        assert_eq!(moved_state, same_state);
+        assert_eq!(moved_state.blank_position(), same_state.blank_position());
+        assert_eq!(
+            moved_state.create_neighbour_move_state(Direction::Left),
+            same_state.create_neighbour_move_state(Direction::Left)
+        );
+    }
+
+    #[test]
+    fn toroidal_moves_wrap() {
+        let puzzle_state =
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solved().with_topology(Topology::Toroidal);
+
+        assert!([
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right
+        ]
+        .into_iter()
+        .all(|direction| puzzle_state.can_move(direction)));
+        assert_eq!(4, puzzle_state.neighbours().len());
+
+        let moved_state = puzzle_state.create_neighbour_move_state(Direction::Right);
+
+        assert_eq!("[1, 2, 3, 4, 5, 6, , 8, 7]", moved_state.to_string());
+        assert_eq!(BoardCoordinates::new(2, 0), moved_state.blank_position());
+        assert_eq!(Topology::Toroidal, moved_state.topology());
+        assert_eq!(
+            puzzle_state,
+            moved_state.create_neighbour_move_state(Direction::Left)
+        );
+        assert_ne!(PuzzleState::solved(), puzzle_state);
+    }
+
+    #[test]
+    fn toroidal_solvability() {
+        let swapped_state = "[2, 1, 3, 4, 5, 6, 7, 8, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap();
+
+        assert!(!swapped_state.is_solvable());
+        assert!(swapped_state
+            .with_topology(Topology::Toroidal)
+            .is_solvable());
+
+        // Board of even size stays bipartite, so parity check still applies.
+        let swapped_state = "[2, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, ]"
+            .parse::<PuzzleState<BIGGEST_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_topology(Topology::Toroidal);
+
+        assert!(!swapped_state.is_solvable());
+    }
+
     #[test]
     fn parse_succesfull() {
         let puzzle_state_str = "[1, 4, 2, 3, , 5, 6, 7, 8]";
@@ -622,5 +1183,10 @@ mod tests {
         let puzzle_state_str = puzzle_state.to_string();
 
         assert_eq!("[1, 4, 2, 3, , 5, 6, 7, 8]", &puzzle_state_str);
+
+        let puzzle_state_str = "[1, , 2, 3, , 5, 6, 7, 4]";
+        let puzzle_state: PuzzleState<BIGGER_PUZZLE_SIZE> = puzzle_state_str.parse().unwrap();
+
+        assert_eq!(puzzle_state_str, puzzle_state.to_string());
     }
 }
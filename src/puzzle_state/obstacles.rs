@@ -0,0 +1,354 @@
+/*!
+* [Obstacles] are board cells which no tile can enter. Every obstacle cell holds the number which
+* belongs there in solved state and that number never moves. Obstacles are part of board like
+* [Topology](super::topology::Topology), they are set by [PuzzleState::with_obstacles] and moves
+* of such state never enter them.
+*
+* Moves are still transpositions with blank, so [PuzzleState::is_solvable] check is necessary for
+* solvability, but not sufficient (e.g. obstacles can split board or create dead ends).
+* [PuzzleState::solvability] searches whole state space for boards with at most
+* [MAX_EXHAUSTIVE_FREE_CELLS] free cells and returns [Solvability::Unknown] for bigger ones.
+* States with several blanks have to reach [PuzzleState::solved_with_blanks].
+*/
+
+use std::collections::{HashSet, VecDeque};
+
+use super::coordinates::BoardCoordinates;
+use super::locked_cells::LockedCells;
+use super::puzzle_move::DIRECTIONS;
+use super::topology::Topology;
+use super::PuzzleState;
+
+/// Maximum number of free cells for which solvability is decided by exhaustive search.
+pub const MAX_EXHAUSTIVE_FREE_CELLS: usize = 9;
+
+/// Errors that can occur when creating [Obstacles].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ObstaclesError {
+    /// Cell of blank in solved state can't be blocked.
+    BlankCellBlocked,
+}
+
+/// Result of solvability check on board with obstacles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solvability {
+    /// Solved state can be reached.
+    Solvable,
+    /// Solved state can't be reached.
+    Unsolvable,
+    /// Necessary conditions hold, but board is too big for exhaustive search.
+    Unknown,
+}
+
+/// Set of blocked board cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Obstacles<const PUZZLE_SIZE: usize> {
+    locked_cells: LockedCells<PUZZLE_SIZE>,
+}
+
+// API impl block
+impl<const PUZZLE_SIZE: usize> Obstacles<PUZZLE_SIZE> {
+    /// Creates [Obstacles] blocking given `cells`.
+    pub fn new(cells: &[BoardCoordinates<PUZZLE_SIZE>]) -> Result<Self, ObstaclesError> {
+        let blank_coordinates = Self::solved_blank_coordinates();
+
+        if cells.contains(&blank_coordinates) {
+            return Err(ObstaclesError::BlankCellBlocked);
+        }
+
+        let locked_cells = cells
+            .iter()
+            .fold(LockedCells::new(), |locked_cells, &coordinates| {
+                locked_cells.with_cell(coordinates)
+            });
+
+        Ok(Obstacles { locked_cells })
+    }
+
+    /// Checks if cell at `coordinates` is blocked.
+    pub fn contains(&self, coordinates: BoardCoordinates<PUZZLE_SIZE>) -> bool {
+        self.locked_cells.contains(coordinates)
+    }
+
+    /// Checks if there are no obstacles.
+    pub fn is_empty(&self) -> bool {
+        self.locked_cells.is_empty()
+    }
+
+    /// Returns blocked cells as [LockedCells].
+    pub fn locked_cells(&self) -> LockedCells<PUZZLE_SIZE> {
+        self.locked_cells
+    }
+
+    /// Returns number of cells which are not blocked.
+    pub fn free_cells_count(&self) -> usize {
+        Self::all_coordinates()
+            .filter(|&coordinates| !self.contains(coordinates))
+            .count()
+    }
+
+    /**
+     * Returns shortest distances between free cells going around obstacles on board with given
+     * `topology`, indexed by cell indices (row by row). Distance is `None` if cells aren't
+     * connected or one of them is blocked.
+     */
+    pub fn cell_distances(&self, topology: Topology) -> Vec<Vec<Option<u8>>> {
+        Self::all_coordinates()
+            .map(|coordinates| self.distances_from(coordinates, topology))
+            .collect()
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> Obstacles<PUZZLE_SIZE> {
+    /// Checks if cell with `cell_index` (row by row) is blocked.
+    pub(super) fn contains_index(&self, cell_index: usize) -> bool {
+        self.locked_cells.contains_index(cell_index)
+    }
+
+    fn solved_blank_coordinates() -> BoardCoordinates<PUZZLE_SIZE> {
+        BoardCoordinates::new((PUZZLE_SIZE - 1) as u8, (PUZZLE_SIZE - 1) as u8)
+    }
+
+    fn all_coordinates() -> impl Iterator<Item = BoardCoordinates<PUZZLE_SIZE>> {
+        (0..PUZZLE_SIZE * PUZZLE_SIZE).map(|cell_index| {
+            BoardCoordinates::new(
+                (cell_index / PUZZLE_SIZE) as u8,
+                (cell_index % PUZZLE_SIZE) as u8,
+            )
+        })
+    }
+
+    fn cell_index(coordinates: BoardCoordinates<PUZZLE_SIZE>) -> usize {
+        let (row, column) = coordinates.as_tuple();
+
+        row as usize * PUZZLE_SIZE + column as usize
+    }
+
+    /// BFS over free cells from `start`, cells are adjacent according to `topology`.
+    fn distances_from(
+        &self,
+        start: BoardCoordinates<PUZZLE_SIZE>,
+        topology: Topology,
+    ) -> Vec<Option<u8>> {
+        let mut distances = vec![None; PUZZLE_SIZE * PUZZLE_SIZE];
+
+        if self.contains(start) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::from([start]);
+        distances[Self::cell_index(start)] = Some(0);
+
+        while let Some(coordinates) = queue.pop_front() {
+            let distance = distances[Self::cell_index(coordinates)].unwrap_or_default();
+
+            for direction in DIRECTIONS {
+                let Some(neighbour) = topology
+                    .neighbour(coordinates, direction)
+                    .filter(|&neighbour| !self.contains(neighbour))
+                else {
+                    continue;
+                };
+                let neighbour_distance = &mut distances[Self::cell_index(neighbour)];
+
+                if neighbour_distance.is_none() {
+                    *neighbour_distance = Some(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
+    /**
+     * Decides if solved state can be reached from this state on its board. Without obstacles it
+     * is decided by [PuzzleState::is_solvable].
+     */
+    pub fn solvability(&self) -> Solvability {
+        let obstacles = self.obstacles();
+
+        if !self.is_solvable() {
+            return Solvability::Unsolvable;
+        } else if obstacles.is_empty() {
+            return Solvability::Solvable;
+        }
+
+        let solved_state = self.solved_board_state();
+        let obstacles_in_place = Obstacles::all_coordinates()
+            .filter(|&coordinates| obstacles.contains(coordinates))
+            .all(|coordinates| self.number_at(coordinates) == solved_state.number_at(coordinates));
+
+        if !obstacles_in_place || !self.targets_reachable() {
+            Solvability::Unsolvable
+        } else if obstacles.free_cells_count() <= MAX_EXHAUSTIVE_FREE_CELLS {
+            if self.reachable_from_solved() {
+                Solvability::Solvable
+            } else {
+                Solvability::Unsolvable
+            }
+        } else {
+            Solvability::Unknown
+        }
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
+    /// Returns solved state with the same number of blanks on the same board.
+    fn solved_board_state(&self) -> Self {
+        PuzzleState::solved_with_blanks(self.blanks_count())
+            .with_topology(self.topology())
+            .with_obstacles(self.obstacles())
+    }
+
+    /// Checks if every number (and single blank) can reach its solved cell around obstacles.
+    fn targets_reachable(&self) -> bool {
+        let obstacles = self.obstacles();
+        let blanks_count = self.blanks_count();
+        let solved_state = self.solved_board_state();
+
+        Obstacles::all_coordinates().all(|coordinates| {
+            let number = self.number_at(coordinates);
+
+            if number.is_none() && blanks_count > 1 {
+                return true;
+            }
+
+            let target = solved_state.number_coordinates(number);
+
+            let distances = obstacles.distances_from(coordinates, self.topology());
+
+            coordinates == target || distances[Obstacles::cell_index(target)].is_some()
+        })
+    }
+
+    /// Searches all states reachable from solved state for this state.
+    fn reachable_from_solved(&self) -> bool {
+        let solved_state = self.solved_board_state();
+        let mut visited_states = HashSet::from([solved_state]);
+        let mut queue = VecDeque::from([solved_state]);
+
+        while let Some(visited_state) = queue.pop_front() {
+            if visited_state == *self {
+                return true;
+            }
+
+            for neighbour in visited_state.neighbours() {
+                let (_, neighbour_state) = neighbour.into_direction_and_puzzle_state();
+
+                if visited_states.insert(neighbour_state) {
+                    queue.push_back(neighbour_state);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::puzzle_state::direction::Direction;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const BIGGEST_PUZZLE_SIZE: usize = 4;
+
+    /// 3x3 board with blocked centre.
+    fn ring_obstacles() -> Obstacles<BIGGER_PUZZLE_SIZE> {
+        Obstacles::new(&[BoardCoordinates::new(1, 1)]).unwrap()
+    }
+
+    #[test]
+    fn blank_cell_cant_be_blocked() {
+        assert_eq!(
+            Err(ObstaclesError::BlankCellBlocked),
+            Obstacles::<BIGGER_PUZZLE_SIZE>::new(&[BoardCoordinates::new(2, 2)])
+        );
+    }
+
+    #[test]
+    fn moves_respect_obstacles() {
+        let obstacles = ring_obstacles();
+
+        assert_eq!(8, obstacles.free_cells_count());
+
+        let puzzle_state = "[1, , 3, 4, 5, 6, 7, 2, 8]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+        let directions: Vec<_> = puzzle_state
+            .neighbours()
+            .map(|neighbour| neighbour.into_direction_and_puzzle_state().0)
+            .collect();
+
+        assert!(!puzzle_state.can_move(Direction::Up));
+        assert!(!puzzle_state.can_move(Direction::Down));
+        assert!(puzzle_state.can_move(Direction::Left));
+        assert_eq!(vec![Direction::Left, Direction::Right], directions);
+    }
+
+    #[test]
+    fn distances_go_around_obstacles() {
+        let cell_distances = ring_obstacles().cell_distances(Topology::Flat);
+
+        // From top middle to bottom middle around the ring.
+        assert_eq!(Some(4), cell_distances[1][7]);
+        assert_eq!(Some(2), cell_distances[0][2]);
+        assert_eq!(None, cell_distances[0][4]);
+
+        // On toroidal board bottom middle is just above top middle.
+        let cell_distances = ring_obstacles().cell_distances(Topology::Toroidal);
+
+        assert_eq!(Some(1), cell_distances[1][7]);
+        assert_eq!(Some(1), cell_distances[0][2]);
+        assert_eq!(None, cell_distances[0][4]);
+    }
+
+    #[test]
+    fn solvability_works() {
+        let obstacles = ring_obstacles();
+
+        // Tiles on ring can only rotate, so only one cyclic order is reachable.
+        let rotated_state = "[4, 1, 2, 7, 5, 3, 8, 6, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+        let swapped_state = "[2, 1, 3, 4, 5, 6, 8, 7, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+        let reordered_state = "[1, 3, 2, 4, 5, 6, 8, 7, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+        let moved_obstacle_state = "[1, 2, 3, 5, 4, 6, 8, 7, ]"
+            .parse::<PuzzleState<BIGGER_PUZZLE_SIZE>>()
+            .unwrap()
+            .with_obstacles(obstacles);
+
+        assert!(reordered_state.is_solvable());
+        assert_eq!(Solvability::Solvable, rotated_state.solvability());
+        assert_eq!(Solvability::Unsolvable, swapped_state.solvability());
+        assert_eq!(Solvability::Unsolvable, reordered_state.solvability());
+        assert_eq!(Solvability::Unsolvable, moved_obstacle_state.solvability());
+    }
+
+    #[test]
+    fn solvability_of_big_boards_is_unknown() {
+        let obstacles =
+            Obstacles::<BIGGEST_PUZZLE_SIZE>::new(&[BoardCoordinates::new(1, 1)]).unwrap();
+
+        assert_eq!(
+            Solvability::Unknown,
+            PuzzleState::solved()
+                .with_obstacles(obstacles)
+                .solvability()
+        );
+    }
+}
@@ -1,11 +1,15 @@
 //! Move structure used to keep track of how we move on board when solving sliding puzzle.
 
+use super::coordinates::BoardCoordinates;
+use super::locked_cells::LockedCells;
 use super::{Direction, PuzzleState};
 
 /// Move of blank in sliding puzzle game.
+#[derive(Debug, Clone, Copy)]
 pub struct Move<const PUZZLE_SIZE: usize> {
     direction: Direction,
     obtained_state: PuzzleState<PUZZLE_SIZE>,
+    blank_position: BoardCoordinates<PUZZLE_SIZE>,
 }
 
 impl<const PUZZLE_SIZE: usize> Move<PUZZLE_SIZE> {
@@ -14,11 +18,179 @@ impl<const PUZZLE_SIZE: usize> Move<PUZZLE_SIZE> {
         (self.direction, self.obtained_state)
     }
 
-    /// Creates new instance of [Move].
-    pub fn new(direction: Direction, obtained_state: PuzzleState<PUZZLE_SIZE>) -> Self {
+    /**
+     * Creates new instance of [Move], where `blank_position` is position of moved blank in
+     * `obtained_state`.
+     */
+    pub fn new(
+        direction: Direction,
+        obtained_state: PuzzleState<PUZZLE_SIZE>,
+        blank_position: BoardCoordinates<PUZZLE_SIZE>,
+    ) -> Self {
         Move {
             direction,
             obtained_state,
+            blank_position,
         }
     }
+
+    /**
+     * Accessor for `blank_position` field, position of moved blank in obtained state (it differs
+     * from [PuzzleState::blank_position] if other blank is before it).
+     */
+    pub fn blank_position(&self) -> BoardCoordinates<PUZZLE_SIZE> {
+        self.blank_position
+    }
+
+    /// Returns number of tile which was moved into previous cell of blank.
+    pub fn moved_tile(&self) -> u8 {
+        let moved_tile_coordinates = self
+            .obtained_state
+            .topology()
+            .neighbour(self.blank_position, self.direction.opposite())
+            .expect("Blank has to come from board cell.");
+
+        self.obtained_state
+            .number_at(moved_tile_coordinates)
+            .expect("Tile has to be moved to blank cell.")
+    }
+}
+
+/// Directions in order in which neighbours are generated.
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/**
+* Iterator over moves from [PuzzleState], it doesn't allocate.
+* There are at most 4 moves for every blank, blanks are moved in order of their cells.
+* Moves of blank into [LockedCells] or into other blank are skipped.
+*/
+#[derive(Debug, Clone)]
+pub struct Neighbours<const PUZZLE_SIZE: usize> {
+    puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    /// Cell index of currently moved blank.
+    blank_index: u8,
+    next_direction_index: usize,
+    locked_cells: LockedCells<PUZZLE_SIZE>,
+    /// Cells of all blanks, empty for state with single blank.
+    blank_cells: u16,
+    /// Cells of blanks which are moved after current one.
+    next_blank_cells: u16,
+}
+
+impl<const PUZZLE_SIZE: usize> Neighbours<PUZZLE_SIZE> {
+    /// Creates new instance of [Neighbours] of `puzzle_state`.
+    pub fn new(puzzle_state: PuzzleState<PUZZLE_SIZE>) -> Self {
+        let mut neighbours = Neighbours {
+            puzzle_state,
+            blank_index: puzzle_state.blank_index,
+            next_direction_index: 0,
+            locked_cells: LockedCells::new(),
+            blank_cells: 0,
+            next_blank_cells: 0,
+        };
+
+        if puzzle_state.blanks_count() > 1 {
+            neighbours.blank_cells = puzzle_state.blank_cells_mask();
+            neighbours.next_blank_cells = neighbours.blank_cells;
+            neighbours.move_to_next_blank();
+        }
+
+        neighbours
+    }
+
+    /// Skips moves which would move tiles from `locked_cells`.
+    pub fn with_locked_cells(self, locked_cells: LockedCells<PUZZLE_SIZE>) -> Self {
+        Neighbours {
+            locked_cells,
+            ..self
+        }
+    }
+
+    /// Checks if blank can move in `direction` without entering locked cell or other blank.
+    fn is_allowed(&self, direction: Direction) -> bool {
+        if !self
+            .puzzle_state
+            .can_move_blank_index(self.blank_index, direction)
+        {
+            return false;
+        }
+
+        if self.locked_cells.is_empty() && self.blank_cells == 0 {
+            return true;
+        }
+
+        let target_index = self
+            .puzzle_state
+            .blank_neighbour_index(self.blank_index, direction);
+
+        !self.locked_cells.contains_index(target_index)
+            && self.blank_cells & (1 << target_index) == 0
+    }
+
+    /// Starts moving next blank, returns `false` if there is none.
+    fn move_to_next_blank(&mut self) -> bool {
+        if self.next_blank_cells == 0 {
+            return false;
+        }
+
+        self.blank_index = self.next_blank_cells.trailing_zeros() as u8;
+        self.next_blank_cells &= self.next_blank_cells - 1;
+        self.next_direction_index = 0;
+
+        true
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> Iterator for Neighbours<PUZZLE_SIZE> {
+    type Item = Move<PUZZLE_SIZE>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while let Some(&direction) = DIRECTIONS.get(self.next_direction_index) {
+                self.next_direction_index += 1;
+
+                if self.is_allowed(direction) {
+                    let blank_index = self
+                        .puzzle_state
+                        .blank_neighbour_index(self.blank_index, direction);
+
+                    return Some(Move::new(
+                        direction,
+                        self.puzzle_state
+                            .create_blank_index_move_state(self.blank_index, direction),
+                        PuzzleState::cell_coordinates(blank_index),
+                    ));
+                }
+            }
+
+            if !self.move_to_next_blank() {
+                return None;
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut remaining_neighbours = self.clone();
+        let mut remaining_moves = 0;
+
+        loop {
+            remaining_moves += DIRECTIONS[remaining_neighbours.next_direction_index..]
+                .iter()
+                .filter(|&&direction| remaining_neighbours.is_allowed(direction))
+                .count();
+
+            if !remaining_neighbours.move_to_next_blank() {
+                break;
+            }
+        }
+
+        (remaining_moves, Some(remaining_moves))
+    }
 }
+
+impl<const PUZZLE_SIZE: usize> ExactSizeIterator for Neighbours<PUZZLE_SIZE> {}
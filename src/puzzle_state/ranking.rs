@@ -0,0 +1,354 @@
+/*!
+* Mapping of solvable puzzle states to dense indices and back.
+*
+* Rank consists of blank index (counted from bottom right corner, so solved state has rank 0)
+* and Lehmer code of numbers without blank. Parity of numbers permutation is determined by blank
+* position for solvable states, so the last nonzero Lehmer code digit (which is 0 or 1) is
+* dropped and computed from parity when unranking. It makes ranks contiguous over `N! / 2`
+* solvable states.
+*/
+
+use super::obstacles::Obstacles;
+use super::topology::Topology;
+use super::{PuzzleState, BLANK_NUMBER, MAX_NUMBER_WIDTH};
+
+impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
+    /// Returns number of solvable states (`N! / 2` for `N` board cells).
+    pub fn solvable_states_count() -> u64 {
+        (1..=(PUZZLE_SIZE * PUZZLE_SIZE) as u64).product::<u64>() / 2
+    }
+
+    /**
+     * Calculates index of state among all solvable states, in range
+     * `0..`[solvable_states_count](Self::solvable_states_count). Solved state has rank 0.
+     *
+     * Unsolvable state gets rank of solvable state with two last numbers swapped. Rank ignores
+     * obstacles and it is the same on toroidal board of even size, where the same states are
+     * solvable.
+     *
+     * # Panics
+     *
+     * If state has several blanks or it is on toroidal board of odd size (all states are
+     * solvable there, so ranks wouldn't be unique).
+     */
+    pub fn rank(&self) -> u64 {
+        assert!(
+            !self.topology.wraps::<PUZZLE_SIZE>() || PUZZLE_SIZE.is_multiple_of(2),
+            "States on toroidal board of odd size can't be ranked."
+        );
+
+        let internal_numbers = (0..PUZZLE_SIZE * PUZZLE_SIZE).map(|cell_index| {
+            let internal_number = (self.numbers >> (MAX_NUMBER_WIDTH * cell_index)) & 0b1111;
+
+            (internal_number != BLANK_NUMBER).then_some(internal_number)
+        });
+
+        Self::rank_internal_numbers(internal_numbers, false)
+            .expect("Only states with single blank can be ranked.")
+    }
+
+    /// Creates solvable state with given `rank` or `None` if `rank` is out of range.
+    pub fn unrank(rank: u64) -> Option<Self> {
+        if rank >= Self::solvable_states_count() {
+            return None;
+        }
+
+        let cells_count = PUZZLE_SIZE * PUZZLE_SIZE;
+        let numbers_count = cells_count - 1;
+        let blank_index = cells_count - 1 - (rank / Self::numbers_ranks_count()) as usize;
+
+        // Lehmer code digits in reverse order, the last digit is always 0.
+        let mut numbers_rank = (rank % Self::numbers_ranks_count()) << 1;
+        let mut lehmer_digits = [0_u64; 16];
+
+        for (digit_index, lehmer_digit) in lehmer_digits.iter_mut().take(numbers_count).enumerate()
+        {
+            let base = (digit_index + 1) as u64;
+
+            *lehmer_digit = numbers_rank % base;
+            numbers_rank /= base;
+        }
+
+        if numbers_count >= 2 {
+            let digits_parity = lehmer_digits[..numbers_count].iter().sum::<u64>() % 2;
+
+            lehmer_digits[1] = digits_parity ^ Self::numbers_inversions_parity(blank_index);
+        }
+
+        let mut numbers_left = (1_u32 << numbers_count) - 1;
+        let mut numbers = 0_u64;
+        let mut number_ordinal = 0;
+
+        for cell_index in 0..cells_count {
+            let internal_number = if cell_index == blank_index {
+                BLANK_NUMBER
+            } else {
+                let lehmer_digit = lehmer_digits[numbers_count - 1 - number_ordinal];
+                let internal_number = Self::nth_set_bit(numbers_left, lehmer_digit as u32);
+
+                numbers_left &= !(1 << internal_number);
+                number_ordinal += 1;
+
+                internal_number as u64
+            };
+
+            numbers |= internal_number << (MAX_NUMBER_WIDTH * cell_index);
+        }
+
+        Some(PuzzleState {
+            numbers,
+            blank_index: blank_index as u8,
+            topology: Topology::Flat,
+            obstacles: Obstacles::default(),
+        })
+    }
+}
+
+// Private impl block
+impl<const PUZZLE_SIZE: usize> PuzzleState<PUZZLE_SIZE> {
+    /**
+     * Calculates [rank](Self::rank) straight from readable `numbers`, without creating the state.
+     * Returns `None` if `numbers` aren't a solvable state with single blank.
+     */
+    pub(crate) fn rank_readable_numbers(
+        numbers: &[[Option<u8>; PUZZLE_SIZE]; PUZZLE_SIZE],
+    ) -> Option<u64> {
+        let internal_numbers = numbers
+            .iter()
+            .flatten()
+            .map(|number| number.map(|number_value| (number_value as u64).wrapping_sub(1)));
+
+        Self::rank_internal_numbers(internal_numbers, true)
+    }
+
+    /**
+     * Calculates rank from internal numbers of cells row by row (`None` for blank).
+     * Returns `None` if they aren't permutation with single blank, or if permutation is not
+     * solvable and `solvable_only` is set.
+     */
+    fn rank_internal_numbers(
+        internal_numbers: impl Iterator<Item = Option<u64>>,
+        solvable_only: bool,
+    ) -> Option<u64> {
+        let cells_count = PUZZLE_SIZE * PUZZLE_SIZE;
+        let numbers_count = cells_count - 1;
+        let mut numbers_left = (1_u32 << numbers_count) - 1;
+        let mut blank_index = None;
+        let mut numbers_rank = 0_u64;
+        let mut digits_parity = 0;
+        let mut number_ordinal = 0;
+
+        for (cell_index, internal_number) in internal_numbers.enumerate() {
+            let Some(internal_number) = internal_number else {
+                if blank_index.replace(cell_index).is_some() {
+                    return None;
+                }
+
+                continue;
+            };
+
+            if internal_number >= numbers_count as u64 || numbers_left & (1 << internal_number) == 0
+            {
+                return None;
+            }
+
+            let smaller_numbers_mask = (1 << internal_number) - 1;
+            let lehmer_digit = (numbers_left & smaller_numbers_mask).count_ones() as u64;
+
+            numbers_rank = numbers_rank * ((numbers_count - number_ordinal) as u64) + lehmer_digit;
+            numbers_left &= !(1 << internal_number);
+            digits_parity ^= lehmer_digit & 1;
+            number_ordinal += 1;
+        }
+
+        let blank_index = blank_index?;
+
+        // Sum of Lehmer code digits is number of inversions.
+        if solvable_only && digits_parity != Self::numbers_inversions_parity(blank_index) {
+            return None;
+        }
+
+        Some(
+            ((cells_count - 1 - blank_index) as u64) * Self::numbers_ranks_count()
+                + (numbers_rank >> 1),
+        )
+    }
+
+    /// Returns number of ranks of numbers permutations for one blank position (`(N - 1)! / 2`).
+    fn numbers_ranks_count() -> u64 {
+        Self::solvable_states_count() / ((PUZZLE_SIZE * PUZZLE_SIZE) as u64)
+    }
+
+    /**
+     * Returns parity of inversions count of numbers (without blank) for solvable state with blank
+     * at `blank_index`. Blank is treated as the biggest number in parity check, so it adds one
+     * inversion for each cell after it, and whole permutation has parity of blank distance to
+     * bottom right corner.
+     */
+    fn numbers_inversions_parity(blank_index: usize) -> u64 {
+        let blank_row = blank_index / PUZZLE_SIZE;
+        let blank_column = blank_index % PUZZLE_SIZE;
+        let blank_distance = (PUZZLE_SIZE - 1 - blank_row) + (PUZZLE_SIZE - 1 - blank_column);
+        let cells_after_blank = PUZZLE_SIZE * PUZZLE_SIZE - 1 - blank_index;
+
+        ((blank_distance + cells_after_blank) % 2) as u64
+    }
+
+    /// Returns index of `n`-th (counted from 0) set bit in `bits`.
+    fn nth_set_bit(mut bits: u32, n: u32) -> u32 {
+        for _ in 0..n {
+            bits &= bits - 1;
+        }
+
+        bits.trailing_zeros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use crate::heuristics::validate::exact_distances;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+    const BIGGEST_PUZZLE_SIZE: usize = 4;
+
+    fn assert_ranks_are_dense<const SIZE: usize>() {
+        let states_count = PuzzleState::<SIZE>::solvable_states_count();
+        let mut ranks = HashSet::new();
+
+        for puzzle_state in exact_distances::<SIZE>().into_keys() {
+            let rank = puzzle_state.rank();
+
+            assert!(rank < states_count);
+            assert!(ranks.insert(rank));
+            assert_eq!(Some(puzzle_state), PuzzleState::unrank(rank));
+        }
+
+        assert_eq!(states_count, ranks.len() as u64);
+    }
+
+    #[test]
+    fn solvable_states_count_works() {
+        assert_eq!(12, PuzzleState::<PUZZLE_SIZE>::solvable_states_count());
+        assert_eq!(
+            181_440,
+            PuzzleState::<BIGGER_PUZZLE_SIZE>::solvable_states_count()
+        );
+        assert_eq!(
+            10_461_394_944_000,
+            PuzzleState::<BIGGEST_PUZZLE_SIZE>::solvable_states_count()
+        );
+    }
+
+    #[test]
+    fn solved_state_has_rank_zero() {
+        assert_eq!(0, PuzzleState::<PUZZLE_SIZE>::solved().rank());
+        assert_eq!(0, PuzzleState::<BIGGER_PUZZLE_SIZE>::solved().rank());
+        assert_eq!(0, PuzzleState::<BIGGEST_PUZZLE_SIZE>::solved().rank());
+        assert_eq!(
+            Some(PuzzleState::<BIGGEST_PUZZLE_SIZE>::solved()),
+            PuzzleState::unrank(0)
+        );
+    }
+
+    #[test]
+    fn ranks_are_dense_for_small_boards() {
+        assert_ranks_are_dense::<PUZZLE_SIZE>();
+        assert_ranks_are_dense::<BIGGER_PUZZLE_SIZE>();
+    }
+
+    #[test]
+    fn unranked_states_round_trip() {
+        let states_count = PuzzleState::<BIGGEST_PUZZLE_SIZE>::solvable_states_count();
+        let step = states_count / 10_007;
+
+        for rank in (0..states_count)
+            .step_by(step as usize)
+            .chain([states_count - 1])
+        {
+            let puzzle_state = PuzzleState::<BIGGEST_PUZZLE_SIZE>::unrank(rank).unwrap();
+
+            assert!(puzzle_state.is_solvable());
+            assert!(PuzzleState::new(puzzle_state.readable_numbers()).is_ok());
+            assert_eq!(rank, puzzle_state.rank());
+        }
+
+        for rank in 0..PuzzleState::<PUZZLE_SIZE>::solvable_states_count() {
+            let puzzle_state = PuzzleState::<PUZZLE_SIZE>::unrank(rank).unwrap();
+
+            assert_eq!(rank, puzzle_state.rank());
+        }
+    }
+
+    #[test]
+    fn readable_numbers_rank_works() {
+        for puzzle_state in exact_distances::<BIGGER_PUZZLE_SIZE>().into_keys() {
+            assert_eq!(
+                Some(puzzle_state.rank()),
+                PuzzleState::rank_readable_numbers(&puzzle_state.readable_numbers())
+            );
+        }
+
+        let unsolvable_numbers = [[Some(2), Some(1)], [Some(3), None]];
+        let several_blanks_numbers = [[Some(1), None], [Some(2), None]];
+        let repeated_numbers = [[Some(1), Some(1)], [Some(3), None]];
+        let too_big_numbers = [[Some(1), Some(2)], [Some(4), None]];
+
+        assert_eq!(
+            None,
+            PuzzleState::<PUZZLE_SIZE>::rank_readable_numbers(&unsolvable_numbers)
+        );
+        assert_eq!(
+            None,
+            PuzzleState::<PUZZLE_SIZE>::rank_readable_numbers(&several_blanks_numbers)
+        );
+        assert_eq!(
+            None,
+            PuzzleState::<PUZZLE_SIZE>::rank_readable_numbers(&repeated_numbers)
+        );
+        assert_eq!(
+            None,
+            PuzzleState::<PUZZLE_SIZE>::rank_readable_numbers(&too_big_numbers)
+        );
+    }
+
+    #[test]
+    fn ranked_states_round_trip() {
+        let puzzle_state: PuzzleState<BIGGEST_PUZZLE_SIZE> =
+            "[12, 1, 10, 2, 7, 11, 4, 14, 5, , 9, 15, 8, 13, 6, 3]"
+                .parse()
+                .unwrap();
+
+        assert_eq!(Some(puzzle_state), PuzzleState::unrank(puzzle_state.rank()));
+    }
+
+    #[test]
+    fn unrank_out_of_range() {
+        assert_eq!(None, PuzzleState::<PUZZLE_SIZE>::unrank(12));
+        assert_eq!(None, PuzzleState::<BIGGEST_PUZZLE_SIZE>::unrank(u64::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn odd_toroidal_state_is_not_ranked() {
+        PuzzleState::<BIGGER_PUZZLE_SIZE>::solved()
+            .with_topology(Topology::Toroidal)
+            .rank();
+    }
+
+    #[test]
+    fn unsolvable_state_rank() {
+        let unsolvable_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), Some(5), Some(6)],
+            [Some(8), Some(7), None],
+        ])
+        .unwrap();
+
+        assert_eq!(0, unsolvable_state.rank());
+    }
+}
@@ -0,0 +1,86 @@
+//! [Topology] of sliding puzzle board, which decides which board cells are adjacent.
+
+use super::coordinates::BoardCoordinates;
+use super::direction::Direction;
+
+/// Topology of sliding puzzle board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Board edges block moves.
+    #[default]
+    Flat,
+    /**
+     * Moving off one edge wraps to the opposite edge. Board of size 2 is the same as flat one,
+     * because neighbour across edge is also the regular neighbour.
+     */
+    Toroidal,
+}
+
+impl Topology {
+    /// Checks if moves wrap around edges of board of `PUZZLE_SIZE` size.
+    pub fn wraps<const PUZZLE_SIZE: usize>(&self) -> bool {
+        *self == Topology::Toroidal && PUZZLE_SIZE > 2
+    }
+
+    /// Returns coordinates of neighbouring cell in given `direction` (`None` at flat board edge).
+    pub fn neighbour<const PUZZLE_SIZE: usize>(
+        &self,
+        coordinates: BoardCoordinates<PUZZLE_SIZE>,
+        direction: Direction,
+    ) -> Option<BoardCoordinates<PUZZLE_SIZE>> {
+        if self.wraps::<PUZZLE_SIZE>() {
+            Some(coordinates.wrapping_neighbour(direction))
+        } else {
+            coordinates.neighbour(direction)
+        }
+    }
+
+    /// Calculates number of moves needed to get from `from` cell to `to` cell.
+    pub fn distance<const PUZZLE_SIZE: usize>(
+        &self,
+        from: &BoardCoordinates<PUZZLE_SIZE>,
+        to: &BoardCoordinates<PUZZLE_SIZE>,
+    ) -> u8 {
+        if self.wraps::<PUZZLE_SIZE>() {
+            from.toroidal_distance(to)
+        } else {
+            from.manhattan_distance(to)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE_SIZE: usize = 2;
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    #[test]
+    fn toroidal_topology_wraps() {
+        let coordinates = BoardCoordinates::<BIGGER_PUZZLE_SIZE>::new(0, 0);
+        let other_coordinates = BoardCoordinates::new(2, 2);
+
+        assert_eq!(None, Topology::Flat.neighbour(coordinates, Direction::Up));
+        assert_eq!(
+            Some(BoardCoordinates::new(2, 0)),
+            Topology::Toroidal.neighbour(coordinates, Direction::Up)
+        );
+        assert_eq!(4, Topology::Flat.distance(&coordinates, &other_coordinates));
+        assert_eq!(
+            2,
+            Topology::Toroidal.distance(&coordinates, &other_coordinates)
+        );
+    }
+
+    #[test]
+    fn small_toroidal_board_is_flat() {
+        let coordinates = BoardCoordinates::<PUZZLE_SIZE>::new(0, 0);
+
+        assert!(!Topology::Toroidal.wraps::<PUZZLE_SIZE>());
+        assert_eq!(
+            None,
+            Topology::Toroidal.neighbour(coordinates, Direction::Left)
+        );
+    }
+}
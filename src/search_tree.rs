@@ -0,0 +1,303 @@
+/*!
+* Recording part of A* search explored by solver and exporting it as
+* [Graphviz DOT](https://graphviz.org/doc/info/lang.html) graph.
+* Nodes are labelled by board and g, h, f values, edges by [Direction] of blank move and
+* solution path is highlighted.
+*/
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::astar_state::AstarState;
+use crate::observer::SearchObserver;
+use crate::puzzle_state::coordinates::BoardCoordinates;
+use crate::{Direction, PuzzleState};
+
+/// Expanded state recorded by [SearchTreeRecorder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTreeNode<const PUZZLE_SIZE: usize> {
+    /// Expanded state.
+    pub puzzle_state: PuzzleState<PUZZLE_SIZE>,
+    /// Direction of move from parent state (`None` for initial state).
+    pub last_direction: Option<Direction>,
+    /// Position of blank moved from parent state.
+    pub moved_blank: BoardCoordinates<PUZZLE_SIZE>,
+    /// Distance from initial state.
+    pub g_value: u8,
+    /// Heuristic value.
+    pub h_value: u8,
+}
+
+/// Observer recording at most `max_nodes` expanded states and found solution.
+#[derive(Debug, Clone)]
+pub struct SearchTreeRecorder<const PUZZLE_SIZE: usize> {
+    max_nodes: usize,
+    nodes: Vec<SearchTreeNode<PUZZLE_SIZE>>,
+    truncated: bool,
+    solution: Option<Vec<(Direction, u8)>>,
+}
+
+impl<const PUZZLE_SIZE: usize> SearchTreeRecorder<PUZZLE_SIZE> {
+    /// Creates new instance of [SearchTreeRecorder] recording at most `max_nodes` states.
+    pub fn new(max_nodes: usize) -> Self {
+        SearchTreeRecorder {
+            max_nodes,
+            nodes: vec![],
+            truncated: false,
+            solution: None,
+        }
+    }
+
+    /// Accessor for `nodes` field (in order of expansion).
+    pub fn nodes(&self) -> &[SearchTreeNode<PUZZLE_SIZE>] {
+        &self.nodes
+    }
+
+    /// Checks if some expanded states weren't recorded because of node limit.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Writes recorded search tree as DOT graph.
+    pub fn write_dot(&self, writer: &mut impl Write) -> io::Result<()> {
+        let recorded_states: HashSet<_> = self.nodes.iter().map(|node| node.puzzle_state).collect();
+        let solution_states = self.solution_states();
+
+        writeln!(writer, "digraph search_tree {{")?;
+        writeln!(writer, "    node [shape=box, fontname=\"monospace\"];")?;
+
+        if self.truncated {
+            writeln!(
+                writer,
+                "    // Only first {} expanded states are recorded.",
+                self.max_nodes
+            )?;
+        }
+
+        for node in &self.nodes {
+            let style = if solution_states.contains(&node.puzzle_state) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+
+            writeln!(
+                writer,
+                "    {} [label=\"{}\\ng={} h={} f={}\"{style}];",
+                node_id(node.puzzle_state),
+                board_label(node.puzzle_state),
+                node.g_value,
+                node.h_value,
+                node.g_value + node.h_value,
+            )?;
+        }
+
+        for node in &self.nodes {
+            let Some(direction) = node.last_direction else {
+                continue;
+            };
+            let Some(parent_state) = node
+                .puzzle_state
+                .create_blank_move_state(node.moved_blank, direction.opposite())
+            else {
+                continue;
+            };
+
+            if !recorded_states.contains(&parent_state) {
+                continue;
+            }
+
+            let style = if solution_states.contains(&node.puzzle_state)
+                && solution_states.contains(&parent_state)
+            {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+
+            writeln!(
+                writer,
+                "    {} -> {} [label=\"{direction:?}\"{style}];",
+                node_id(parent_state),
+                node_id(node.puzzle_state),
+            )?;
+        }
+
+        writeln!(writer, "}}")
+    }
+
+    /// Returns states on found solution path.
+    fn solution_states(&self) -> HashSet<PuzzleState<PUZZLE_SIZE>> {
+        let (Some(steps), Some(initial_node)) = (&self.solution, self.nodes.first()) else {
+            return HashSet::new();
+        };
+
+        let mut curr_state = initial_node.puzzle_state;
+        let mut solution_states = HashSet::from([curr_state]);
+
+        for &(direction, moved_tile) in steps {
+            curr_state = curr_state
+                .create_tile_move_state(moved_tile, direction)
+                .expect("Solution has to consist of possible moves.");
+            solution_states.insert(curr_state);
+        }
+
+        solution_states
+    }
+}
+
+impl<const PUZZLE_SIZE: usize> SearchObserver<PUZZLE_SIZE> for SearchTreeRecorder<PUZZLE_SIZE> {
+    fn on_expand(&mut self, astar_state: &AstarState<PUZZLE_SIZE>) {
+        if self.nodes.len() == self.max_nodes {
+            self.truncated = true;
+            return;
+        }
+
+        self.nodes.push(SearchTreeNode {
+            puzzle_state: astar_state.puzzle_state(),
+            last_direction: astar_state.last_direction(),
+            moved_blank: astar_state.moved_blank(),
+            g_value: astar_state.distance_from_start(),
+            h_value: astar_state.heuristic_value(),
+        });
+    }
+
+    fn on_solution(&mut self, steps: &[Direction], moved_tiles: &[u8]) {
+        self.solution = Some(
+            steps
+                .iter()
+                .copied()
+                .zip(moved_tiles.iter().copied())
+                .collect(),
+        );
+    }
+}
+
+/// Packed numbers identify state uniquely (also with several blanks), so they are node identifier.
+fn node_id<const PUZZLE_SIZE: usize>(puzzle_state: PuzzleState<PUZZLE_SIZE>) -> String {
+    format!("s{:x}", puzzle_state.packed_numbers())
+}
+
+/// Returns board rows separated by DOT line breaks, blank is shown as `_`.
+fn board_label<const PUZZLE_SIZE: usize>(puzzle_state: PuzzleState<PUZZLE_SIZE>) -> String {
+    puzzle_state
+        .readable_numbers()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|number| match number {
+                    Some(number) => format!("{number:>2}"),
+                    None => " _".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::heuristics::ManhattanDistance;
+    use crate::{solve_with_observer, SearchOptions};
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    fn puzzle_state() -> PuzzleState<BIGGER_PUZZLE_SIZE> {
+        PuzzleState::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(6)],
+            [Some(7), Some(5), Some(8)],
+        ])
+        .unwrap()
+    }
+
+    fn dot(max_nodes: usize) -> (SearchTreeRecorder<BIGGER_PUZZLE_SIZE>, String) {
+        let mut recorder = SearchTreeRecorder::new(max_nodes);
+
+        solve_with_observer(
+            puzzle_state(),
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut recorder,
+        )
+        .unwrap();
+
+        let mut dot = vec![];
+        recorder.write_dot(&mut dot).unwrap();
+
+        (recorder, String::from_utf8(dot).unwrap())
+    }
+
+    #[test]
+    fn dot_export_works() {
+        let (recorder, dot) = dot(100);
+
+        assert_eq!(3, recorder.nodes().len());
+        assert!(!recorder.is_truncated());
+        assert!(dot.starts_with("digraph search_tree {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(" 1  2  3\\n 4  _  6\\n 7  5  8\\ng=0 h=2 f=2"));
+        assert!(dot.contains(&format!(
+            "{} [label=\" 1  2  3\\n 4  5  6\\n 7  8  _\\ng=2 h=0 f=2\", color=red, penwidth=2];",
+            node_id(PuzzleState::<BIGGER_PUZZLE_SIZE>::solved())
+        )));
+        assert_eq!(2, dot.matches(" -> ").count());
+        assert_eq!(5, dot.matches("color=red").count());
+        assert!(dot.contains("[label=\"Down\", color=red, penwidth=2];"));
+    }
+
+    #[test]
+    fn node_cap_works() {
+        let (recorder, dot) = dot(1);
+
+        assert_eq!(1, recorder.nodes().len());
+        assert!(recorder.is_truncated());
+        assert!(dot.contains("// Only first 1 expanded states are recorded."));
+        assert_eq!(0, dot.matches(" -> ").count());
+    }
+
+    #[test]
+    fn several_blanks_dot_export_works() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::new_with_blanks([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(6)],
+            [Some(7), Some(5), None],
+        ])
+        .unwrap();
+        let mut recorder = SearchTreeRecorder::new(100);
+
+        solve_with_observer(
+            puzzle_state,
+            &ManhattanDistance::new(),
+            SearchOptions::new(),
+            &mut recorder,
+        )
+        .unwrap();
+
+        let mut dot = vec![];
+        recorder.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+
+        let node_ids: HashSet<_> = recorder
+            .nodes()
+            .iter()
+            .map(|node| node_id(node.puzzle_state))
+            .collect();
+
+        assert_eq!(recorder.nodes().len(), node_ids.len());
+        assert!(dot.contains(" 1  2  3\\n 4  5  6\\n 7  _  _\\ng=1 h=0 f=1"));
+        assert_eq!(3, dot.matches("color=red").count());
+
+        for edge in dot.lines().filter(|line| line.contains(" -> ")) {
+            let (parent_id, rest) = edge.trim().split_once(" -> ").unwrap();
+            let (child_id, _) = rest.split_once(' ').unwrap();
+
+            assert_ne!(parent_id, child_id);
+            assert!(node_ids.contains(parent_id));
+            assert!(node_ids.contains(child_id));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Statistics collected by A* search.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+const BRANCHING_FACTOR_PRECISION: f64 = 1e-6;
+
+/// Counters describing how A* search went.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchStats {
+    expanded_states: usize,
+    generated_states: usize,
+    duplicate_hits: usize,
+    reopened_states: usize,
+    peak_frontier_size: usize,
+    expanded_states_per_f_value: BTreeMap<u8, usize>,
+    heuristic_evaluations: usize,
+    initial_heuristic_value: u8,
+    solution_length: usize,
+    effective_branching_factor: Option<f64>,
+    #[serde(rename = "wall_time_seconds", serialize_with = "serialize_seconds")]
+    wall_time: Duration,
+}
+
+impl SearchStats {
+    /// Creates empty statistics for search from state with `initial_heuristic_value`.
+    pub(crate) fn new(initial_heuristic_value: u8) -> Self {
+        SearchStats {
+            initial_heuristic_value,
+            heuristic_evaluations: 1,
+            ..SearchStats::default()
+        }
+    }
+
+    /// Records expansion of state with `f_value`.
+    pub(crate) fn record_expansion(&mut self, f_value: u8) {
+        self.expanded_states += 1;
+        *self.expanded_states_per_f_value.entry(f_value).or_default() += 1;
+    }
+
+    /// Records generation of new state (its heuristic is evaluated).
+    pub(crate) fn record_generation(&mut self) {
+        self.generated_states += 1;
+        self.heuristic_evaluations += 1;
+    }
+
+    /// Records finding already visited state.
+    pub(crate) fn record_duplicate(&mut self) {
+        self.duplicate_hits += 1;
+    }
+
+    /// Records current frontier size.
+    pub(crate) fn record_frontier_size(&mut self, frontier_size: usize) {
+        self.peak_frontier_size = self.peak_frontier_size.max(frontier_size);
+    }
+
+    /// Records search time of search stopped before finding solution.
+    pub(crate) fn stop(&mut self, wall_time: Duration) {
+        self.wall_time = wall_time;
+    }
+
+    /// Records found solution length and search time.
+    pub(crate) fn finish(&mut self, solution_length: usize, wall_time: Duration) {
+        self.solution_length = solution_length;
+        self.wall_time = wall_time;
+        self.effective_branching_factor =
+            effective_branching_factor(self.generated_states, solution_length);
+    }
+
+    /// Accessor for `expanded_states` field.
+    pub fn expanded_states(&self) -> usize {
+        self.expanded_states
+    }
+
+    /// Accessor for `generated_states` field.
+    pub fn generated_states(&self) -> usize {
+        self.generated_states
+    }
+
+    /// Returns number of times already visited state was generated or taken from frontier.
+    pub fn duplicate_hits(&self) -> usize {
+        self.duplicate_hits
+    }
+
+    /**
+     * Returns number of visited states opened again after shorter path to them was found. It is
+     * always 0, A* skips visited states instead of reopening them (so found solutions are optimal
+     * only for consistent heuristics).
+     */
+    pub fn reopened_states(&self) -> usize {
+        self.reopened_states
+    }
+
+    /// Accessor for `peak_frontier_size` field.
+    pub fn peak_frontier_size(&self) -> usize {
+        self.peak_frontier_size
+    }
+
+    /// Returns number of expanded states for each f value.
+    pub fn expanded_states_per_f_value(&self) -> &BTreeMap<u8, usize> {
+        &self.expanded_states_per_f_value
+    }
+
+    /// Accessor for `heuristic_evaluations` field.
+    pub fn heuristic_evaluations(&self) -> usize {
+        self.heuristic_evaluations
+    }
+
+    /// Accessor for `initial_heuristic_value` field.
+    pub fn initial_heuristic_value(&self) -> u8 {
+        self.initial_heuristic_value
+    }
+
+    /// Accessor for `solution_length` field.
+    pub fn solution_length(&self) -> usize {
+        self.solution_length
+    }
+
+    /**
+     * Returns branching factor `b` of uniform tree with solution depth, which would have the same
+     * number of generated states (`N = b + b^2 + ... + b^d`). `None` for empty solution.
+     */
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        self.effective_branching_factor
+    }
+
+    /// Accessor for `wall_time` field.
+    pub fn wall_time(&self) -> Duration {
+        self.wall_time
+    }
+
+    /// Returns statistics as JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Statistics are always serializable.")
+    }
+}
+
+/// Calculates effective branching factor using bisection.
+fn effective_branching_factor(generated_states: usize, solution_length: usize) -> Option<f64> {
+    if solution_length == 0 {
+        return None;
+    }
+
+    let generated_states = generated_states as f64;
+    let tree_size = |branching_factor: f64| {
+        (1..=solution_length)
+            .map(|depth| branching_factor.powi(depth as i32))
+            .sum::<f64>()
+    };
+
+    let mut low = 0.0;
+    let mut high = generated_states.max(1.0);
+
+    while high - low > BRANCHING_FACTOR_PRECISION {
+        let middle = (low + high) / 2.0;
+
+        if tree_size(middle) < generated_states {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    Some((low + high) / 2.0)
+}
+
+fn serialize_seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_branching_factor_works() {
+        assert_eq!(None, effective_branching_factor(10, 0));
+
+        let branching_factor = effective_branching_factor(2 + 4 + 8, 3).unwrap();
+
+        assert!((branching_factor - 2.0).abs() < 1e-4);
+
+        let branching_factor = effective_branching_factor(5, 5).unwrap();
+
+        assert!((branching_factor - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn records_work() {
+        let mut stats = SearchStats::new(5);
+
+        stats.record_expansion(5);
+        stats.record_generation();
+        stats.record_generation();
+        stats.record_duplicate();
+        stats.record_frontier_size(2);
+        stats.record_expansion(7);
+        stats.record_expansion(7);
+        stats.record_frontier_size(1);
+        stats.finish(1, Duration::from_millis(1500));
+
+        assert_eq!(3, stats.expanded_states());
+        assert_eq!(2, stats.generated_states());
+        assert_eq!(1, stats.duplicate_hits());
+        assert_eq!(0, stats.reopened_states());
+        assert_eq!(2, stats.peak_frontier_size());
+        assert_eq!(
+            &BTreeMap::from([(5, 1), (7, 2)]),
+            stats.expanded_states_per_f_value()
+        );
+        assert_eq!(3, stats.heuristic_evaluations());
+        assert_eq!(5, stats.initial_heuristic_value());
+    }
+
+    #[test]
+    fn json_export_works() {
+        let mut stats = SearchStats::new(3);
+
+        stats.record_expansion(3);
+        stats.finish(0, Duration::from_millis(1500));
+
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+
+        assert_eq!(1, json["expanded_states"]);
+        assert_eq!(0, json["reopened_states"]);
+        assert_eq!(1, json["expanded_states_per_f_value"]["3"]);
+        assert_eq!(3, json["initial_heuristic_value"]);
+        assert_eq!(1.5, json["wall_time_seconds"]);
+        assert!(json["effective_branching_factor"].is_null());
+    }
+}
@@ -0,0 +1,409 @@
+/*!
+* Drawing puzzle board in terminal and interactive [Game] play.
+* Only ANSI escape sequences (through crossterm) are used, so it works in plain terminals.
+*/
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Print, Stylize};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::game::Game;
+use crate::heuristics::Heuristic;
+use crate::{Direction, PuzzleState};
+
+const CONTROLS_HELP: &str = "Arrows/WASD: move blank  U: undo  R: redo  H: hint  Q: quit";
+const STEP_CONTROLS_HELP: &str = "Any key: next move  Q: quit";
+const DELAY_CONTROLS_HELP: &str = "Q: quit";
+const FINISHED_HELP: &str = "Press any key to exit.";
+/// Maximum number of states visited when searching for hint.
+const HINT_BUDGET: usize = 2_000_000;
+
+/// How solution playback advances to next move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playback {
+    /// Next move is shown after given delay.
+    Delay(Duration),
+    /// Next move is shown after key press.
+    KeyPress,
+}
+
+/**
+* Returns lines of board drawing. Tile with `highlighted_number` is drawn in reverse video.
+*/
+pub fn board_lines<const PUZZLE_SIZE: usize>(
+    puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    highlighted_number: Option<u8>,
+) -> Vec<String> {
+    let separator_line = format!("+{}", "----+".repeat(PUZZLE_SIZE));
+    let mut lines = vec![separator_line.clone()];
+
+    for numbers_row in puzzle_state.readable_numbers() {
+        let mut line = String::from("|");
+
+        for number in numbers_row {
+            let tile = number.map_or("    ".to_string(), |number_value| {
+                format!(" {number_value:>2} ")
+            });
+
+            if number.is_some() && number == highlighted_number {
+                line += &tile.reverse().to_string();
+            } else {
+                line += &tile;
+            }
+
+            line += "|";
+        }
+
+        lines.push(line);
+        lines.push(separator_line.clone());
+    }
+
+    lines
+}
+
+/**
+* Returns number which was moved when blank moved in `direction` and `puzzle_state` was obtained
+* (it is next to blank in opposite direction).
+*/
+pub fn moved_number<const PUZZLE_SIZE: usize>(
+    puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    direction: Direction,
+) -> Option<u8> {
+    puzzle_state
+        .topology()
+        .neighbour(puzzle_state.blank_position(), direction.opposite())
+        .and_then(|previous_blank| puzzle_state.number_at(previous_blank))
+}
+
+/// Clears screen and draws `lines` from its top left corner.
+pub fn draw_lines(writer: &mut impl Write, lines: &[String]) -> io::Result<()> {
+    queue!(writer, Clear(ClearType::All))?;
+
+    for (row, line) in lines.iter().enumerate() {
+        queue!(writer, MoveTo(0, row as u16), Print(line))?;
+    }
+
+    writer.flush()
+}
+
+/// Runs `f` in alternate screen with raw mode enabled and restores terminal afterwards.
+pub fn with_raw_terminal<T>(f: impl FnOnce(&mut io::Stdout) -> io::Result<T>) -> io::Result<T> {
+    let mut stdout = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+
+    let result = f(&mut stdout);
+
+    execute!(stdout, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    result
+}
+
+/// Waits for key press (key releases and other events are skipped).
+pub fn read_key_press() -> io::Result<KeyEvent> {
+    loop {
+        if let Event::Key(key_event) = event::read()? {
+            if key_event.kind == KeyEventKind::Press {
+                return Ok(key_event);
+            }
+        }
+    }
+}
+
+/// Checks if key is used to quit (`q`, `Esc` or `Ctrl+C`).
+pub fn is_quit_key(key_event: &KeyEvent) -> bool {
+    match key_event.code {
+        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key_event.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+/**
+* Lets user play `game` in terminal until quit. Hints are computed by solving game from current
+* state with `heuristic`.
+*/
+pub fn play<const PUZZLE_SIZE: usize>(
+    game: &mut Game<PUZZLE_SIZE>,
+    heuristic: &dyn Heuristic<PUZZLE_SIZE>,
+) -> io::Result<()> {
+    with_raw_terminal(|stdout| {
+        let mut message = String::new();
+
+        loop {
+            draw_lines(stdout, &game_lines(game, &message))?;
+
+            let key_event = read_key_press()?;
+
+            if is_quit_key(&key_event) {
+                return Ok(());
+            }
+
+            message = match key_event.code {
+                KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => {
+                    move_message(game, Direction::Up)
+                }
+                KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => {
+                    move_message(game, Direction::Down)
+                }
+                KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('A') => {
+                    move_message(game, Direction::Left)
+                }
+                KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('D') => {
+                    move_message(game, Direction::Right)
+                }
+                KeyCode::Char('u') | KeyCode::Char('U') => {
+                    if game.undo() {
+                        String::new()
+                    } else {
+                        "Nothing to undo.".to_string()
+                    }
+                }
+                KeyCode::Char('r') | KeyCode::Char('R') => {
+                    if game.redo() {
+                        solved_message(game)
+                    } else {
+                        "Nothing to redo.".to_string()
+                    }
+                }
+                KeyCode::Char('h') | KeyCode::Char('H') => {
+                    draw_lines(stdout, &game_lines(game, "Computing hint..."))?;
+
+                    match game.hint(heuristic, HINT_BUDGET) {
+                        Some(hint) if hint.exact => format!(
+                            "Hint: move blank {:?} ({} moves to solution).",
+                            hint.direction, hint.remaining_moves
+                        ),
+                        Some(hint) => format!(
+                            "Hint: move blank {:?} (about {} moves to solution).",
+                            hint.direction, hint.remaining_moves
+                        ),
+                        None if game.is_solved() => solved_message(game),
+                        None => "State is not solvable.".to_string(),
+                    }
+                }
+                _ => message,
+            };
+        }
+    })
+}
+
+/**
+* Shows solution `steps` performed from `initial_state` one by one, highlighting moved tiles.
+* It can be quit at any moment, otherwise it waits for key press after last move.
+*/
+pub fn animate_solution<const PUZZLE_SIZE: usize>(
+    initial_state: PuzzleState<PUZZLE_SIZE>,
+    steps: &[Direction],
+    playback: Playback,
+) -> io::Result<()> {
+    with_raw_terminal(|stdout| {
+        let controls_help = match playback {
+            Playback::Delay(_) => DELAY_CONTROLS_HELP,
+            Playback::KeyPress => STEP_CONTROLS_HELP,
+        };
+        let mut puzzle_state = initial_state;
+
+        draw_lines(
+            stdout,
+            &solution_frame_lines(&puzzle_state, None, 0, steps.len(), controls_help),
+        )?;
+
+        for (move_index, &direction) in steps.iter().enumerate() {
+            let quit = match playback {
+                Playback::Delay(delay) => event::poll(delay)? && is_quit_key(&read_key_press()?),
+                Playback::KeyPress => is_quit_key(&read_key_press()?),
+            };
+
+            if quit {
+                return Ok(());
+            }
+
+            puzzle_state = puzzle_state.create_neighbour_move_state(direction);
+
+            draw_lines(
+                stdout,
+                &solution_frame_lines(
+                    &puzzle_state,
+                    Some(direction),
+                    move_index + 1,
+                    steps.len(),
+                    controls_help,
+                ),
+            )?;
+        }
+
+        draw_lines(
+            stdout,
+            &solution_frame_lines(
+                &puzzle_state,
+                steps.last().copied(),
+                steps.len(),
+                steps.len(),
+                FINISHED_HELP,
+            ),
+        )?;
+        read_key_press()?;
+
+        Ok(())
+    })
+}
+
+/**
+* Returns lines of solution playback screen after `done_moves` of `moves_count` moves, where
+* `last_direction` is direction of last done move.
+*/
+pub fn solution_frame_lines<const PUZZLE_SIZE: usize>(
+    puzzle_state: &PuzzleState<PUZZLE_SIZE>,
+    last_direction: Option<Direction>,
+    done_moves: usize,
+    moves_count: usize,
+    controls_help: &str,
+) -> Vec<String> {
+    let highlighted_number =
+        last_direction.and_then(|direction| moved_number(puzzle_state, direction));
+
+    let mut lines = board_lines(puzzle_state, highlighted_number);
+
+    lines.push(String::new());
+    lines.push(format!("Move: {done_moves}/{moves_count}"));
+    lines.push(format!("Remaining moves: {}", moves_count - done_moves));
+    lines.push(String::new());
+    lines.push(controls_help.to_string());
+
+    lines
+}
+
+/// Returns lines of game screen with `message` below board.
+fn game_lines<const PUZZLE_SIZE: usize>(game: &Game<PUZZLE_SIZE>, message: &str) -> Vec<String> {
+    let puzzle_state = game.puzzle_state();
+    let highlighted_number = game
+        .last_move()
+        .and_then(|direction| {
+            puzzle_state
+                .topology()
+                .neighbour(game.blank_position(), direction.opposite())
+        })
+        .and_then(|coordinates| puzzle_state.number_at(coordinates));
+
+    let mut lines = board_lines(&puzzle_state, highlighted_number);
+
+    lines.push(String::new());
+    lines.push(format!("Moves: {}", game.moves_count()));
+    lines.push(message.to_string());
+    lines.push(String::new());
+    lines.push(CONTROLS_HELP.to_string());
+
+    lines
+}
+
+/// Moves blank and returns message describing result.
+fn move_message<const PUZZLE_SIZE: usize>(
+    game: &mut Game<PUZZLE_SIZE>,
+    direction: Direction,
+) -> String {
+    if game.move_blank(direction) {
+        solved_message(game)
+    } else {
+        format!("Blank can't move {direction:?}.")
+    }
+}
+
+/// Returns congratulations if game is solved or empty message otherwise.
+fn solved_message<const PUZZLE_SIZE: usize>(game: &Game<PUZZLE_SIZE>) -> String {
+    if game.is_solved() {
+        format!("Solved in {} moves!", game.moves_count())
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_state::topology::Topology;
+
+    const BIGGER_PUZZLE_SIZE: usize = 3;
+
+    fn puzzle_state() -> PuzzleState<BIGGER_PUZZLE_SIZE> {
+        PuzzleState::new([
+            [Some(1), Some(2), Some(3)],
+            [Some(4), None, Some(5)],
+            [Some(7), Some(8), Some(6)],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn board_lines_work() {
+        assert_eq!(
+            vec![
+                "+----+----+----+",
+                "|  1 |  2 |  3 |",
+                "+----+----+----+",
+                "|  4 |    |  5 |",
+                "+----+----+----+",
+                "|  7 |  8 |  6 |",
+                "+----+----+----+",
+            ],
+            board_lines(&puzzle_state(), None)
+        );
+    }
+
+    #[test]
+    fn highlighted_tile_drawn_differently() {
+        let lines = board_lines(&puzzle_state(), Some(5));
+
+        assert_ne!("|  4 |    |  5 |", lines[3]);
+        assert!(lines[3].contains("  5 "));
+        assert_eq!("|  1 |  2 |  3 |", lines[1]);
+    }
+
+    #[test]
+    fn solution_frame_lines_work() {
+        let lines = solution_frame_lines(&puzzle_state(), None, 2, 5, STEP_CONTROLS_HELP);
+
+        assert_eq!(board_lines(&puzzle_state(), None), lines[..7]);
+        assert_eq!(
+            vec![
+                "",
+                "Move: 2/5",
+                "Remaining moves: 3",
+                "",
+                STEP_CONTROLS_HELP
+            ],
+            lines[7..]
+        );
+
+        let lines =
+            solution_frame_lines(&puzzle_state(), Some(Direction::Left), 2, 5, FINISHED_HELP);
+
+        assert_eq!(board_lines(&puzzle_state(), Some(5)), lines[..7]);
+    }
+
+    #[test]
+    fn moved_number_works() {
+        assert_eq!(Some(5), moved_number(&puzzle_state(), Direction::Left));
+        assert_eq!(Some(2), moved_number(&puzzle_state(), Direction::Down));
+    }
+
+    #[test]
+    fn moved_number_wraps_around_toroidal_board() {
+        let puzzle_state = PuzzleState::<BIGGER_PUZZLE_SIZE>::solved()
+            .with_topology(Topology::Toroidal)
+            .create_neighbour_move_state(Direction::Down);
+
+        assert_eq!(Some(3), moved_number(&puzzle_state, Direction::Down));
+
+        let lines = solution_frame_lines(&puzzle_state, Some(Direction::Down), 1, 1, FINISHED_HELP);
+
+        assert_eq!(board_lines(&puzzle_state, Some(3)), lines[..7]);
+    }
+}